@@ -0,0 +1,41 @@
+//! End to end grading time, `cargo bench` reports essays per second for each
+//! size so regressions in the linting pipeline show up as a drop
+use criterion::{Criterion, Throughput, criterion_group, criterion_main};
+use quickgrade::{Config, grade_text};
+use std::hint::black_box;
+
+const PARAGRAPH: &str = "My video is about cats and why they make grate pets. \
+Cats sleep for most of the day, but at night they like to play with string and chase shadows. \
+i think every family should have one because they are quiet, clean and easy to look after. \
+They do not need walks like dogs do, and they keep mice away from the house.\n\n";
+
+// About 70 words a paragraph
+fn essay(paragraphs: usize) -> String {
+    format!("{}https://youtu.be/abc123\n", PARAGRAPH.repeat(paragraphs))
+}
+
+fn grading(c: &mut Criterion) {
+    let config = Config {
+        auto_questions: true,
+        ..Default::default()
+    };
+    // loads harper's curated dictionary once so no size pays for it
+    grade_text(&essay(1), &config);
+    let mut group = c.benchmark_group("grade_text");
+    group.throughput(Throughput::Elements(1));
+    for (name, paragraphs) in [("small", 1), ("medium", 10), ("large", 40)] {
+        let text = essay(paragraphs);
+        group.bench_function(name, |b| {
+            b.iter(|| grade_text(black_box(&text), &config).score_percent())
+        });
+    }
+    group.finish();
+}
+
+criterion_group! {
+    name = benches;
+    // the large essay takes a while per run
+    config = Criterion::default().sample_size(10);
+    targets = grading
+}
+criterion_main!(benches);
@@ -0,0 +1,104 @@
+//! --seed-answers: the manual questions answer for each file, so a batch can
+//! run without prompting
+use crate::input;
+use std::path::Path;
+
+#[derive(clap::ValueEnum, Clone, Copy, Debug, PartialEq)]
+pub enum Answer {
+    Yes,
+    No,
+}
+
+// file -> answered all the questions
+pub struct Answers {
+    path: String,
+    answers: Vec<(String, bool)>,
+}
+
+fn parse_answer(text: &str) -> Option<bool> {
+    match text.trim().to_lowercase().as_str() {
+        "y" | "yes" | "true" | "pass" | "1" => Some(true),
+        "n" | "no" | "false" | "fail" | "0" => Some(false),
+        _ => None,
+    }
+}
+
+impl Answers {
+    // A .json file holds an object of file -> yes/no (or true/false), anything
+    // else is a file,answer CSV read like a --baseline one
+    pub fn load(path: &Path) -> Result<Answers, String> {
+        let text = std::fs::read_to_string(path)
+            .map_err(|e| format!("can't read answers {}: {}", path.display(), e))?;
+        let answers = if path.extension().is_some_and(|e| e == "json") {
+            Answers::parse_json(&text).map_err(|e| format!("{}: {}", path.display(), e))?
+        } else {
+            Answers::parse_csv(&text).map_err(|e| format!("{}:{}", path.display(), e))?
+        };
+        Ok(Answers {
+            path: path.display().to_string(),
+            answers,
+        })
+    }
+    fn parse_json(text: &str) -> Result<Vec<(String, bool)>, String> {
+        let map: serde_json::Map<String, serde_json::Value> =
+            serde_json::from_str(text).map_err(|e| e.to_string())?;
+        map.into_iter()
+            .map(|(file, value)| {
+                let answer = match &value {
+                    serde_json::Value::Bool(b) => Some(*b),
+                    serde_json::Value::String(s) => parse_answer(s),
+                    _ => None,
+                };
+                answer
+                    .map(|a| (file.clone(), a))
+                    .ok_or_else(|| format!("{}: '{}' isn't yes or no", file, value))
+            })
+            .collect()
+    }
+    fn parse_csv(text: &str) -> Result<Vec<(String, bool)>, String> {
+        let mut answers = Vec::new();
+        for (i, line) in text.lines().enumerate() {
+            if line.trim().is_empty() || line.trim_start().starts_with('#') {
+                continue;
+            }
+            let fields: Vec<&str> = line
+                .split(',')
+                .map(|f| f.trim().trim_matches('"'))
+                .collect();
+            if fields.len() != 2 {
+                return Err(format!("{}: expected 'file,answer'", i + 1));
+            }
+            match parse_answer(fields[1]) {
+                Some(answer) => answers.push((fields[0].to_string(), answer)),
+                None if answers.is_empty() && i == 0 => {} // header
+                None => return Err(format!("{}: '{}' isn't yes or no", i + 1, fields[1])),
+            }
+        }
+        Ok(answers)
+    }
+    pub fn lookup(&self, name: &str) -> Option<bool> {
+        self.answers
+            .iter()
+            .find(|(file, _)| input::names_file(file, name))
+            .map(|(_, answer)| *answer)
+    }
+    // Warnings for graded files without an answer and answers nobody uses
+    pub fn mismatches(&self, names: &[String], default: Option<Answer>) -> Vec<String> {
+        let fallback = match default {
+            Some(Answer::Yes) => "answering yes",
+            Some(Answer::No) => "answering no",
+            None => "will prompt",
+        };
+        let mut warnings: Vec<String> = names
+            .iter()
+            .filter(|name| self.lookup(name).is_none())
+            .map(|name| format!("{}: no answer for {}, {}", self.path, name, fallback))
+            .collect();
+        for (file, _) in &self.answers {
+            if !names.iter().any(|name| input::names_file(file, name)) {
+                warnings.push(format!("{}: {} isn't being graded", self.path, file));
+            }
+        }
+        warnings
+    }
+}
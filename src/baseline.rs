@@ -0,0 +1,100 @@
+//! --baseline: compare final scores against the expected ones in a CSV file
+use crate::input;
+use std::path::Path;
+
+// file -> expected final score in percent
+pub struct Baseline {
+    expected: Vec<(String, f32)>,
+}
+
+pub struct Comparison {
+    pub table: String,
+    /// Files whose score moved more than the tolerance
+    pub over_tolerance: usize,
+}
+
+impl Baseline {
+    // Two columns, file and expected score (0-100). A header row, blank lines
+    // and lines starting with # are skipped, quotes around fields are dropped
+    pub fn load(path: &Path) -> Result<Baseline, String> {
+        let text = std::fs::read_to_string(path)
+            .map_err(|e| format!("can't read baseline {}: {}", path.display(), e))?;
+        let mut expected = Vec::new();
+        for (i, line) in text.lines().enumerate() {
+            if line.trim().is_empty() || line.trim_start().starts_with('#') {
+                continue;
+            }
+            let fields: Vec<&str> = line
+                .split(',')
+                .map(|f| f.trim().trim_matches('"'))
+                .collect();
+            if fields.len() != 2 {
+                return Err(format!(
+                    "{}:{}: expected 'file,score'",
+                    path.display(),
+                    i + 1
+                ));
+            }
+            match fields[1].trim_end_matches('%').parse::<f32>() {
+                Ok(score) => expected.push((fields[0].to_string(), score)),
+                Err(_) if expected.is_empty() && i == 0 => {} // header
+                Err(_) => {
+                    return Err(format!(
+                        "{}:{}: '{}' isn't a score",
+                        path.display(),
+                        i + 1,
+                        fields[1]
+                    ));
+                }
+            }
+        }
+        Ok(Baseline { expected })
+    }
+    fn lookup(&self, name: &str) -> Option<f32> {
+        self.expected
+            .iter()
+            .find(|(file, _)| input::names_file(file, name))
+            .map(|(_, score)| *score)
+    }
+    pub fn compare(&self, scores: &[(String, f32)], tolerance: f32) -> Comparison {
+        let width = scores
+            .iter()
+            .map(|(n, _)| n.len())
+            .max()
+            .unwrap_or(0)
+            .max(4);
+        let mut table = format!(
+            "{:width$}  {:>8}  {:>6}  {:>6}\n",
+            "file", "expected", "actual", "diff"
+        );
+        let mut over_tolerance = 0;
+        for (name, actual) in scores {
+            match self.lookup(name) {
+                Some(expected) => {
+                    let diff = actual - expected;
+                    let flag = if diff.abs() > tolerance {
+                        over_tolerance += 1;
+                        "  <-"
+                    } else {
+                        ""
+                    };
+                    table += &format!(
+                        "{:width$}  {:>8}  {:>6}  {:>+6}{}\n",
+                        name, expected, actual, diff, flag
+                    );
+                }
+                None => table += &format!("{:width$}  not in baseline\n", name),
+            }
+        }
+        for (file, _) in &self.expected {
+            let graded = scores.iter().any(|(name, _)| input::names_file(file, name));
+            if !graded {
+                table += &format!("{:width$}  in baseline but not graded\n", file);
+            }
+        }
+        Comparison {
+            table,
+            over_tolerance,
+        }
+    }
+}
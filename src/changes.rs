@@ -0,0 +1,34 @@
+//! --changed-only: the lines of a draft that are new or changed since the
+//! previous one, from a line diff on their longest common subsequence
+
+// For each line of current, whether it isn't in the previous draft. Trailing
+// whitespace doesn't count as a change
+pub(crate) fn changed_lines(previous: &str, current: &str) -> Vec<bool> {
+    let old: Vec<&str> = previous.lines().map(str::trim_end).collect();
+    let new: Vec<&str> = current.lines().map(str::trim_end).collect();
+    // common[i][j]: length of the longest common subsequence of old[i..] and new[j..]
+    let mut common = vec![vec![0u32; new.len() + 1]; old.len() + 1];
+    for i in (0..old.len()).rev() {
+        for j in (0..new.len()).rev() {
+            common[i][j] = if old[i] == new[j] {
+                common[i + 1][j + 1] + 1
+            } else {
+                common[i + 1][j].max(common[i][j + 1])
+            };
+        }
+    }
+    let mut changed = vec![true; new.len()];
+    let (mut i, mut j) = (0, 0);
+    while i < old.len() && j < new.len() {
+        if old[i] == new[j] {
+            changed[j] = false;
+            i += 1;
+            j += 1;
+        } else if common[i + 1][j] >= common[i][j + 1] {
+            i += 1;
+        } else {
+            j += 1;
+        }
+    }
+    changed
+}
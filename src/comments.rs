@@ -0,0 +1,72 @@
+//! --comments: the grader's own feedback for each file, put on the report
+//! next to the automated scores
+use crate::input;
+use std::path::Path;
+
+// file -> comment
+pub struct Comments {
+    path: String,
+    comments: Vec<(String, String)>,
+}
+
+impl Comments {
+    // A .json file holds an object of file -> comment, anything else is
+    // file,comment rows where the comment is the rest of the line, commas and all
+    pub fn load(path: &Path) -> Result<Comments, String> {
+        let text = std::fs::read_to_string(path)
+            .map_err(|e| format!("can't read comments {}: {}", path.display(), e))?;
+        let comments = if path.extension().is_some_and(|e| e == "json") {
+            Comments::parse_json(&text).map_err(|e| format!("{}: {}", path.display(), e))?
+        } else {
+            Comments::parse_csv(&text).map_err(|e| format!("{}:{}", path.display(), e))?
+        };
+        Ok(Comments {
+            path: path.display().to_string(),
+            comments,
+        })
+    }
+    fn parse_json(text: &str) -> Result<Vec<(String, String)>, String> {
+        let map: serde_json::Map<String, serde_json::Value> =
+            serde_json::from_str(text).map_err(|e| e.to_string())?;
+        map.into_iter()
+            .map(|(file, value)| match value {
+                serde_json::Value::String(comment) => Ok((file, comment)),
+                _ => Err(format!("{}: '{}' isn't a string", file, value)),
+            })
+            .collect()
+    }
+    fn parse_csv(text: &str) -> Result<Vec<(String, String)>, String> {
+        let mut comments = Vec::new();
+        for (i, line) in text.lines().enumerate() {
+            if line.trim().is_empty() || line.trim_start().starts_with('#') {
+                continue;
+            }
+            let Some((file, comment)) = line.split_once(',') else {
+                return Err(format!("{}: expected 'file,comment'", i + 1));
+            };
+            let file = file.trim().trim_matches('"');
+            let comment = comment.trim().trim_matches('"');
+            // header
+            if i == 0 && file == "file" && comment == "comment" {
+                continue;
+            }
+            comments.push((file.to_string(), comment.to_string()));
+        }
+        Ok(comments)
+    }
+    pub fn lookup(&self, name: &str) -> Option<&str> {
+        self.comments
+            .iter()
+            .find(|(file, _)| input::names_file(file, name))
+            .map(|(_, comment)| comment.as_str())
+            .filter(|comment| !comment.is_empty())
+    }
+    // Comments for files that aren't being graded, most likely typos
+    pub fn unused(&self, names: &[String]) -> Vec<String> {
+        self.comments
+            .iter()
+            .filter(|(file, _)| !names.iter().any(|name| input::names_file(file, name)))
+            .map(|(file, _)| format!("{}: {} isn't being graded", self.path, file))
+            .collect()
+    }
+}
@@ -0,0 +1,1615 @@
+//! Rubric settings: built in defaults, then /etc/quickgrade.toml, then
+//! quickgrade.toml (or --config), then flags
+use crate::{Args, DIALECT, KeyAnswer, LintCategory, WordCount, round_percent};
+use harper_core::linting::LintKind;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::Path;
+
+pub const SYSTEM_CONFIG_FILE: &str = "/etc/quickgrade.toml";
+pub const DEFAULT_CONFIG_FILE: &str = "quickgrade.toml";
+/// What `quickgrade init` writes, every setting at its default with a comment
+pub const CONFIG_TEMPLATE: &str = include_str!("../templates/quickgrade.toml");
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    pub auto_questions: bool,
+    /// Final score in percent below which the questions prompt is skipped when
+    /// even full marks on it can't reach it, off unless set
+    pub skip_manual_if_below: Option<f32>,
+    /// The weighted criteria every submission is graded on, in report order
+    pub categories: Vec<Category>,
+    pub min_paragraphs: usize,
+    /// Sentences with more words than this count as over-long, 0 turns the check off
+    pub max_sentence_words: usize,
+    pub max_long_sentences: usize,
+    /// Regex for the line each answer starts on in a multi-question
+    /// assignment, like `^Q\d+:`. The rest of that line is part of the answer
+    pub split_on: Option<String>,
+    /// Distinct sentences every answer needs, 0 turns the criterion off. The
+    /// whole text is one answer without split_on or a line matching it
+    pub min_sentences_per_answer: usize,
+    /// Words or phrases that fail the submission, matched as whole words ignoring case
+    pub forbidden_words: Vec<String>,
+    /// Distinct transition words needed, 0 turns the criterion off
+    pub min_transitions: usize,
+    /// What counts as a transition, words or phrases matched as whole words ignoring case
+    pub transition_words: Vec<String>,
+    /// Topics the text has to cover, each set is a criterion of its own
+    pub keyword_sets: Vec<KeywordSet>,
+    /// The answers a factual assignment expects, one per line, see
+    /// `KeyAnswer::parse_key`. Off unless set
+    pub answer_key: Option<std::path::PathBuf>,
+    /// The answers in answer_key, read when the config is loaded. Library
+    /// users can set them directly
+    #[serde(skip)]
+    pub expected_answers: Vec<KeyAnswer>,
+    /// Files that are parts of one submission, each graded on its own and
+    /// weighted into the total. Off when empty
+    pub sections: Vec<Section>,
+    /// Hosts that count for the link criterion
+    pub link_domains: Vec<String>,
+    /// Hosts that fail the link criterion outright, even next to a good link
+    pub forbidden_link_domains: Vec<String>,
+    /// Links needed for full marks on the link criterion, each one short costs its share
+    pub min_links: usize,
+    /// First non-empty line has to be a title
+    pub require_title: bool,
+    pub title_strictness: TitleStrictness,
+    /// Titles longer than this don't count
+    pub title_max_words: usize,
+    /// Regex the title line has to match, replaces the heuristics when set
+    pub title_pattern: Option<String>,
+    /// Regex the last non-empty line has to match, e.g. `^Submitted by: `. Off unless set
+    pub closing_pattern: Option<String>,
+    /// Let the closing appear on any line instead of only the last
+    pub closing_anywhere: bool,
+    /// Flesch-Kincaid grade level range, the readability criterion is off unless one is set
+    pub min_grade_level: Option<f32>,
+    pub max_grade_level: Option<f32>,
+    /// Experimental tense check, the share of verbs allowed in the less used
+    /// of past and present tense. Off unless set
+    pub max_tense_mix: Option<f32>,
+    /// Experimental opener check, the share of sentences allowed to start
+    /// with the same word. Off unless set
+    pub max_same_opener: Option<f32>,
+    /// Experimental paragraph balance check, the share of the words allowed
+    /// in the longest paragraph. Off unless set
+    pub max_paragraph_share: Option<f32>,
+    /// Experimental filler heuristic, how many filler_phrases are allowed
+    /// per 100 words. Off unless set
+    pub max_filler_density: Option<f32>,
+    /// What counts as filler, whole words ignoring case
+    pub filler_phrases: Vec<String>,
+    /// Experimental positional weighting, how many times a mistake at the very
+    /// start of the text counts, falling to once at the end. Off unless set
+    pub early_error_weight: Option<f32>,
+    /// The curve early_error_weight falls along
+    pub early_error_decay: Decay,
+    /// Best effort check that every paragraph has a complete sentence, one
+    /// with a subject and a finite verb
+    pub require_complete_paragraphs: bool,
+    /// Every quotation mark has to be closed within its paragraph, apostrophes
+    /// in contractions and possessives aren't quotes
+    pub require_balanced_quotes: bool,
+    /// Point of view the text has to be written in, off unless set
+    pub voice: Option<Voice>,
+    /// First-person pronouns allowed under `voice = "third"`
+    pub max_first_person: usize,
+    pub unevaluated: Unevaluated,
+    /// How the final score and each criterion's points are rounded for display
+    pub rounding: Rounding,
+    /// The harper rules that run, every one by default
+    pub linters: Linters,
+    /// Harper lint kind to the categories it should count against, the
+    /// first one this config has wins over the usual report order
+    pub lint_priority: BTreeMap<String, Vec<String>>,
+    /// Lint categories list items (lines starting with `-`, `*` or `1.`) aren't graded on
+    pub list_exemption: ListExemption,
+    /// Kinds of tokens whose spelling mistakes don't count, like "H2O" or "USD5"
+    pub spelling_ignore: Vec<TokenClass>,
+    /// What British spellings like "colour" are, the dialect is American
+    pub british_spellings: BritishSpellings,
+    /// Links are blanked out before linting, the link criterion still sees them
+    pub strip_urls_before_linting: bool,
+    /// Regex for the line ending an assignment prompt pasted above the answer,
+    /// it and everything above it are left out
+    pub prompt_marker: Option<String>,
+    /// The assignment prompt, paragraphs at the top that match it are left out
+    pub prompt_file: Option<std::path::PathBuf>,
+    /// The text of prompt_file, read when the config is loaded. Library users
+    /// can set it directly
+    #[serde(skip)]
+    pub prompt: Option<String>,
+    /// The previous draft from --changed-only, only lints on the lines
+    /// added or changed since it count. Library users can set it directly
+    #[serde(skip)]
+    pub previous: Option<String>,
+    /// Whether "mother-in-law" is one word or three, in every word count
+    pub hyphenated_words: HyphenatedWords,
+    /// Whether "don't" is one word or two, in every word count
+    pub contractions: Contractions,
+    /// Printed at the top of every report. Not part of the rubric, so they're
+    /// left out of the config hash
+    #[serde(skip_serializing)]
+    pub assignment: Option<String>,
+    #[serde(skip_serializing)]
+    pub due_date: Option<String>,
+    #[serde(skip_serializing)]
+    pub grader: Option<String>,
+    /// Criteria the text report lists, in this order. Empty lists them all in
+    /// report order. Only changes how it looks, so left out of the hash too
+    #[serde(skip_serializing)]
+    pub report_order: Vec<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize, clap::ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum TitleStrictness {
+    /// Short, starts with a capital and doesn't end in a period
+    Loose,
+    /// Like loose but every word over three letters is capitalized too
+    Strict,
+}
+// How a score like 79.5 is shown, the same everywhere it is
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize, clap::ValueEnum)]
+#[serde(rename_all = "kebab-case")]
+pub enum Rounding {
+    /// To the nearest whole percent, halves up: 79.5 is 80
+    #[default]
+    Nearest,
+    /// To the nearest whole percent, halves to the even one: 79.5 is 80, 78.5 is 78
+    NearestEven,
+    /// Down, a borderline score is never rounded up: 79.9 is 79
+    Floor,
+    /// Up: 79.1 is 80
+    Ceil,
+}
+impl Rounding {
+    pub(crate) fn apply(self, percent: f32) -> f32 {
+        // the weighted shares add up to things like 79.49999, which is a half
+        let percent = (percent * 1000.0).round() / 1000.0;
+        match self {
+            Rounding::Nearest => percent.round(),
+            Rounding::NearestEven => percent.round_ties_even(),
+            Rounding::Floor => percent.floor(),
+            Rounding::Ceil => percent.ceil(),
+        }
+    }
+}
+
+// What a criterion that couldn't be evaluated (no link_domains to look for,
+// say) does to the final score
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize, clap::ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum Unevaluated {
+    /// Counts as 0 and keeps its weight
+    Zero,
+    /// Left out, the other criteria share its weight
+    #[default]
+    Excluded,
+}
+
+// List items are often fragments, not sentences
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize, clap::ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum ListExemption {
+    /// Graded like any other line
+    None,
+    /// Capitalization mistakes on them don't count
+    #[default]
+    Capitalization,
+    /// Neither capitalization nor punctuation mistakes count
+    Both,
+}
+impl ListExemption {
+    pub fn exempts(self, category: Option<LintCategory>) -> bool {
+        match (self, category) {
+            (ListExemption::None, _) | (_, None | Some(LintCategory::Spelling)) => false,
+            (ListExemption::Capitalization, Some(c)) => c == LintCategory::Capitalization,
+            (ListExemption::Both, Some(_)) => true,
+        }
+    }
+}
+
+// Tokens harper's dictionary doesn't know but that aren't misspelled words
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize, clap::ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum TokenClass {
+    /// Starting with a digit, like "1,000", "3rd", "1990s" or "50%"
+    Numbers,
+    /// Currency symbols and codes, alone or on an amount, like "$5", "EUR" or "USD5"
+    Currency,
+    /// Letters and digits mixed, like "H2O", "mp3" or "COVID-19"
+    Alphanumeric,
+}
+const CURRENCY_CODES: &[&str] = &[
+    "usd", "eur", "gbp", "jpy", "cny", "inr", "cad", "aud", "chf", "mxn", "brl", "krw",
+];
+impl TokenClass {
+    pub fn matches(self, token: &str) -> bool {
+        match self {
+            TokenClass::Numbers => token.starts_with(|c: char| c.is_ascii_digit()),
+            TokenClass::Currency => {
+                let letters = token
+                    .trim_matches(|c: char| c.is_ascii_digit() || ".,".contains(c))
+                    .to_lowercase();
+                token.contains(['$', '€', '£', '¥']) || CURRENCY_CODES.contains(&letters.as_str())
+            }
+            TokenClass::Alphanumeric => {
+                token.contains(|c: char| c.is_alphabetic())
+                    && token.contains(|c: char| c.is_ascii_digit())
+            }
+        }
+    }
+}
+
+// Harper's dictionary tags spellings like "colour" as British, the American
+// dialect flags them
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize, clap::ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum BritishSpellings {
+    /// Spelling mistakes like any other
+    #[default]
+    Mistakes,
+    /// Correct
+    Accepted,
+    /// Correct, but mixing them with American spellings fails consistent_spelling
+    Consistent,
+}
+
+// The point of view an assignment asks for
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize, clap::ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum Voice {
+    /// At least one first-person pronoun, for personal essays
+    First,
+    /// At most `max_first_person` first-person pronouns
+    Third,
+}
+impl Voice {
+    pub fn description(self) -> &'static str {
+        match self {
+            Voice::First => "Written in the first person",
+            Voice::Third => "Written in the third person",
+        }
+    }
+}
+
+// How a hyphenated word like "mother-in-law" counts toward word counts
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize, clap::ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum HyphenatedWords {
+    /// Each part is a word, three for "mother-in-law"
+    #[default]
+    Parts,
+    /// The whole thing is one word
+    One,
+}
+
+// How a contraction like "don't" counts toward word counts
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize, clap::ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum Contractions {
+    /// One word
+    #[default]
+    One,
+    /// Two words, like "do not"
+    Two,
+}
+
+/// The harper rules quickgrade runs, by the names `--disable` and the
+/// `linters` table take, in the order they run
+pub const LINTERS: &[&str] = &[
+    "Spelling",
+    "AnA",
+    "CapitalizePersonalPronouns",
+    "CommaFixes",
+    "CompoundNouns",
+    "CorrectNumberSuffix",
+    "CurrencyPlacement",
+    "DiscourseMarkers",
+    "EllipsisLength",
+    "HopHope",
+    "ItsContraction",
+    "LetsConfusion",
+    "NounVerbConfusion",
+    "NumberSuffixCapitalization",
+    "PhrasalVerbAsCompoundNoun",
+    "PronounContraction",
+    "UnclosedQuotes",
+    "InflectedVerbAfterTo",
+    "SentenceCapitalization",
+    "Sams Lint",
+];
+// The rules behind spelling, punctuation and capitalization mistakes
+const MECHANICS: &[&str] = &[
+    "Spelling",
+    "CapitalizePersonalPronouns",
+    "CommaFixes",
+    "DiscourseMarkers",
+    "EllipsisLength",
+    "NumberSuffixCapitalization",
+    "UnclosedQuotes",
+    "SentenceCapitalization",
+    "Sams Lint",
+];
+
+// The set of harper rules a `linters` table starts from
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize, clap::ValueEnum)]
+#[serde(rename_all = "kebab-case")]
+pub enum LinterPreset {
+    /// Every rule in `LINTERS`
+    #[default]
+    All,
+    /// Just the spell checker
+    SpellingOnly,
+    /// The spelling, punctuation and capitalization rules, no grammar
+    Mechanics,
+}
+
+/// Which harper rules run: a preset with rules added and taken out by name,
+/// or `only` the rules named. Built up like
+/// `Linters::preset(LinterPreset::Mechanics).disable("CommaFixes")`
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Linters {
+    pub preset: LinterPreset,
+    /// Added to the preset
+    pub enable: Vec<String>,
+    /// Taken out of whatever would run, `only` included
+    pub disable: Vec<String>,
+    /// Replaces the preset and `enable` when it names any
+    pub only: Vec<String>,
+}
+impl Linters {
+    pub fn preset(preset: LinterPreset) -> Linters {
+        Linters {
+            preset,
+            ..Linters::default()
+        }
+    }
+    pub fn only<S: Into<String>>(names: impl IntoIterator<Item = S>) -> Linters {
+        Linters {
+            only: names.into_iter().map(Into::into).collect(),
+            ..Linters::default()
+        }
+    }
+    pub fn enable(mut self, name: &str) -> Linters {
+        self.enable.push(name.to_string());
+        self
+    }
+    pub fn disable(mut self, name: &str) -> Linters {
+        self.disable.push(name.to_string());
+        self
+    }
+    /// The rules that run, in `LINTERS` order. Names that aren't rules are
+    /// ignored here, `check` reports them
+    pub fn rules(&self) -> Vec<&'static str> {
+        let named = |names: &[String], rule: &str| names.iter().any(|n| n == rule);
+        LINTERS
+            .iter()
+            .copied()
+            .filter(|rule| {
+                if !self.only.is_empty() {
+                    return named(&self.only, rule);
+                }
+                named(&self.enable, rule)
+                    || match self.preset {
+                        LinterPreset::All => true,
+                        LinterPreset::SpellingOnly => *rule == "Spelling",
+                        LinterPreset::Mechanics => MECHANICS.contains(rule),
+                    }
+            })
+            .filter(|rule| !named(&self.disable, rule))
+            .collect()
+    }
+    pub fn check(&self) -> Result<(), String> {
+        let mut names = self.enable.iter().chain(&self.disable).chain(&self.only);
+        match names.find(|n| !LINTERS.contains(&n.as_str())) {
+            Some(name) => Err(format!(
+                "linters: unknown rule {}, expected one of {}",
+                name,
+                LINTERS.join(", ")
+            )),
+            None => Ok(()),
+        }
+    }
+}
+
+// How early_error_weight falls off from the start of the text to the end
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize, clap::ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum Decay {
+    /// Evenly, a mistake halfway through counts halfway between the weight and 1
+    #[default]
+    Linear,
+    /// Quickly at first, a mistake halfway through counts the square root of the weight
+    Exponential,
+}
+
+// A named criterion and what feeds it
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Category {
+    pub name: String,
+    /// Defaults to the name
+    #[serde(default)]
+    pub description: String,
+    #[serde(default)]
+    pub check: Check,
+    /// Lint categories (spelling, punctuation, capitalization) or harper lint
+    /// kinds (Grammar, Style, ...) counted against this category
+    #[serde(default)]
+    pub lints: Vec<String>,
+    /// Relative to the other categories, each optional criterion weighs 1
+    #[serde(default = "default_weight")]
+    pub weight: f32,
+    /// Mistakes allowed per word, rounded up (0.01 = one per hundred words)
+    #[serde(default)]
+    pub rate: f32,
+    /// Mistakes allowed however short the text, on top of what rate allows
+    #[serde(default)]
+    pub tolerance: usize,
+    #[serde(default)]
+    pub severity: Severity,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    /// Counts toward the score like any criterion
+    #[default]
+    Fail,
+    /// Its mistakes are still reported but it doesn't change the score
+    Warn,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Check {
+    /// Contains a link to one of `link_domains`
+    Link,
+    /// The complete sentences prompt, or --auto-questions
+    Questions,
+    /// Fails when there are more mistakes from `lints` than `rate` allows
+    #[default]
+    Lints,
+}
+
+// The criteria a config can turn on besides its categories, in report order
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) enum Extra {
+    Paragraphs,
+    Transitions,
+    SentenceLength,
+    AnswerSentences,
+    ForbiddenWords,
+    Title,
+    Closing,
+    Readability,
+    Tense,
+    Openers,
+    ParagraphBalance,
+    Filler,
+    Voice,
+    CompleteParagraphs,
+    Quotes,
+    ConsistentSpelling,
+    /// The keyword set at this index of `keyword_sets`
+    Keywords(usize),
+    AnswerKey,
+}
+
+// harper's discourse markers and a few more that essays lean on
+const TRANSITION_WORDS: &[&str] = &[
+    "however",
+    "therefore",
+    "meanwhile",
+    "furthermore",
+    "nevertheless",
+    "consequently",
+    "thus",
+    "instead",
+    "moreover",
+    "alternatively",
+    "additionally",
+    "subsequently",
+    "accordingly",
+    "otherwise",
+    "conversely",
+    "hence",
+    "indeed",
+    "for example",
+    "for instance",
+    "on the other hand",
+    "in addition",
+    "as a result",
+    "similarly",
+    "in contrast",
+    "finally",
+    "in conclusion",
+];
+
+// Padding that says little, common in generic and generated prose alike
+const FILLER_PHRASES: &[&str] = &[
+    "it is important to note",
+    "it's important to note",
+    "it is worth noting",
+    "it's worth noting",
+    "delve into",
+    "in today's world",
+    "needless to say",
+    "it goes without saying",
+    "at the end of the day",
+    "plays a crucial role",
+    "a testament to",
+    "in the realm of",
+    "when it comes to",
+    "first and foremost",
+    "navigate the complexities",
+    "rich tapestry",
+    "in conclusion",
+];
+
+// What each optional criterion (paragraphs, title, ...) weighs
+pub const EXTRA_WEIGHT: f32 = 1.0;
+
+fn default_weight() -> f32 {
+    EXTRA_WEIGHT
+}
+
+// Passes when enough of the keywords appear, matched as whole words ignoring
+// case. Fewer earn their share
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct KeywordSet {
+    pub name: String,
+    pub keywords: Vec<String>,
+    /// How many have to appear, all of them when unset
+    #[serde(default)]
+    pub min: Option<usize>,
+}
+impl KeywordSet {
+    pub fn needed(&self) -> usize {
+        self.min.unwrap_or(self.keywords.len())
+    }
+    pub fn description(&self) -> String {
+        format!(
+            "Mentions {} of: {}",
+            if self.needed() == self.keywords.len() {
+                "all".to_string()
+            } else {
+                format!("at least {}", self.needed())
+            },
+            self.keywords.join(", ")
+        )
+    }
+}
+
+// One of the files a sectioned assignment needs, e.g. its intro
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Section {
+    pub name: String,
+    /// Glob the file name has to match, e.g. `intro*.txt`
+    pub files: String,
+    /// Relative to the other sections
+    #[serde(default = "default_weight")]
+    pub weight: f32,
+}
+
+// harper has no way to list these
+const LINT_KINDS: [LintKind; 20] = [
+    LintKind::Agreement,
+    LintKind::BoundaryError,
+    LintKind::Capitalization,
+    LintKind::Eggcorn,
+    LintKind::Enhancement,
+    LintKind::Formatting,
+    LintKind::Grammar,
+    LintKind::Malapropism,
+    LintKind::Miscellaneous,
+    LintKind::Nonstandard,
+    LintKind::Punctuation,
+    LintKind::Readability,
+    LintKind::Redundancy,
+    LintKind::Regionalism,
+    LintKind::Repetition,
+    LintKind::Spelling,
+    LintKind::Style,
+    LintKind::Typo,
+    LintKind::Usage,
+    LintKind::WordChoice,
+];
+
+impl Category {
+    fn new(name: &str, description: &str, check: Check, lints: &[&str]) -> Category {
+        Category {
+            name: name.to_string(),
+            description: description.to_string(),
+            check,
+            lints: lints.iter().map(|l| l.to_string()).collect(),
+            weight: default_weight(),
+            rate: 0.0,
+            tolerance: 0,
+            severity: Severity::Fail,
+        }
+    }
+    // Whether a lint of this kind counts against the category
+    pub fn takes(&self, kind: LintKind, category: Option<LintCategory>) -> bool {
+        self.check == Check::Lints
+            && self
+                .lints
+                .iter()
+                .any(|entry| match Category::lint_category(entry) {
+                    Some(c) => category == Some(c),
+                    None => *entry == kind.to_string_key(),
+                })
+    }
+    // Our categories are lowercase and harper's kinds capitalized, so
+    // `spelling` takes typos too and `Spelling` only plain misspellings
+    fn lint_category(entry: &str) -> Option<LintCategory> {
+        clap::ValueEnum::from_str(entry, false).ok()
+    }
+    fn validate(&self) -> Result<(), String> {
+        if self.name.trim().is_empty() {
+            return Err("a category needs a name".to_string());
+        }
+        if self.weight.is_nan() || self.weight < 0.0 {
+            return Err(format!("category {}: weight can't be negative", self.name));
+        }
+        match self.check {
+            Check::Lints if self.lints.is_empty() => {
+                Err(format!("category {}: lints is empty", self.name))
+            }
+            Check::Lints => {
+                for entry in &self.lints {
+                    let known = Category::lint_category(entry).is_some()
+                        || LINT_KINDS.iter().any(|k| k.to_string_key() == *entry);
+                    if !known {
+                        return Err(format!(
+                            "category {}: unknown lint {}, expected spelling, punctuation, capitalization or a harper lint kind ({})",
+                            self.name,
+                            entry,
+                            LINT_KINDS
+                                .iter()
+                                .map(|k| k.to_string_key())
+                                .collect::<Vec<_>>()
+                                .join(", ")
+                        ));
+                    }
+                }
+                Ok(())
+            }
+            _ if !self.lints.is_empty() => Err(format!(
+                "category {}: only lints categories take lints",
+                self.name
+            )),
+            _ => Ok(()),
+        }
+    }
+}
+
+impl Default for Config {
+    fn default() -> Config {
+        Config {
+            auto_questions: false,
+            skip_manual_if_below: None,
+            categories: vec![
+                Category::new(
+                    "link",
+                    "Contains a link to a youtube video",
+                    Check::Link,
+                    &[],
+                ),
+                Category::new(
+                    "spelling",
+                    "No spelling mistakes",
+                    Check::Lints,
+                    &["spelling"],
+                ),
+                Category::new(
+                    "punctuation",
+                    "No punctuation mistakes",
+                    Check::Lints,
+                    &["punctuation"],
+                ),
+                Category::new(
+                    "capitalization",
+                    "No capitalization mistakes",
+                    Check::Lints,
+                    &["capitalization"],
+                ),
+                Category::new(
+                    "questions",
+                    "Answered all the questions in complete sentences",
+                    Check::Questions,
+                    &[],
+                ),
+            ],
+            min_paragraphs: 0,
+            max_sentence_words: 0,
+            max_long_sentences: 0,
+            split_on: None,
+            min_sentences_per_answer: 0,
+            forbidden_words: Vec::new(),
+            min_transitions: 0,
+            transition_words: TRANSITION_WORDS.iter().map(|w| w.to_string()).collect(),
+            keyword_sets: Vec::new(),
+            answer_key: None,
+            expected_answers: Vec::new(),
+            sections: Vec::new(),
+            link_domains: vec![
+                "youtube.com".to_string(),
+                "youtu.be".to_string(),
+                "tiktok.com".to_string(),
+                "youtubeeducation.com".to_string(),
+            ],
+            forbidden_link_domains: Vec::new(),
+            min_links: 1,
+            require_title: false,
+            title_strictness: TitleStrictness::Loose,
+            title_max_words: 10,
+            title_pattern: None,
+            closing_pattern: None,
+            closing_anywhere: false,
+            min_grade_level: None,
+            max_grade_level: None,
+            max_tense_mix: None,
+            max_same_opener: None,
+            max_paragraph_share: None,
+            max_filler_density: None,
+            filler_phrases: FILLER_PHRASES.iter().map(|w| w.to_string()).collect(),
+            early_error_weight: None,
+            early_error_decay: Decay::Linear,
+            require_complete_paragraphs: false,
+            require_balanced_quotes: false,
+            voice: None,
+            max_first_person: 0,
+            unevaluated: Unevaluated::Excluded,
+            rounding: Rounding::Nearest,
+            list_exemption: ListExemption::Capitalization,
+            spelling_ignore: Vec::new(),
+            british_spellings: BritishSpellings::Mistakes,
+            strip_urls_before_linting: false,
+            prompt_marker: None,
+            prompt_file: None,
+            prompt: None,
+            previous: None,
+            hyphenated_words: HyphenatedWords::Parts,
+            contractions: Contractions::One,
+            lint_priority: BTreeMap::new(),
+            linters: Linters::default(),
+            assignment: None,
+            due_date: None,
+            grader: None,
+            report_order: Vec::new(),
+        }
+    }
+}
+
+// One row of --show-rubric
+#[derive(Debug, Serialize)]
+pub struct CriterionInfo {
+    pub name: String,
+    pub description: String,
+    pub enabled: bool,
+    /// Percent of the final score, 0 when disabled
+    pub weight: f32,
+    pub settings: String,
+}
+
+// A section as --show-rubric lists it, graded on every enabled criterion
+#[derive(Debug, Serialize)]
+pub struct SectionInfo {
+    pub name: String,
+    pub files: String,
+    /// Percent of the final score
+    pub weight: f32,
+    pub criteria: Vec<String>,
+}
+
+#[derive(Serialize)]
+struct RubricPreview<'a> {
+    criteria: Vec<CriterionInfo>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    sections: Vec<SectionInfo>,
+    config: &'a Config,
+}
+
+impl Config {
+    fn read_table(path: &Path) -> Result<toml::Table, String> {
+        log::debug!("reading config {}", path.display());
+        let text = std::fs::read_to_string(path)
+            .map_err(|e| format!("can't read config {}: {}", path.display(), e))?;
+        toml::from_str(&text).map_err(|e| format!("bad config {}: {}", path.display(), e))
+    }
+    // Later layers win key by key, nested tables are merged the same way and
+    // everything else (lists included) is replaced whole
+    fn merge(base: &mut toml::Table, layer: toml::Table) {
+        for (key, value) in layer {
+            match (base.get_mut(&key), value) {
+                (Some(toml::Value::Table(base)), toml::Value::Table(layer)) => {
+                    Config::merge(base, layer)
+                }
+                (_, value) => {
+                    base.insert(key, value);
+                }
+            }
+        }
+    }
+    pub(crate) fn load(args: &Args) -> Result<Config, String> {
+        match &args.rubric_json {
+            Some(json) => Config::layered(args, Some(Config::json_table(json)?)),
+            None => Config::load_from(args, args.config.first().map(|p| p.as_path())),
+        }
+    }
+    // Like load but with `file` in place of --config
+    pub(crate) fn load_from(args: &Args, file: Option<&Path>) -> Result<Config, String> {
+        let layer = match file {
+            Some(path) => Some(Config::read_table(path)?),
+            None if Path::new(DEFAULT_CONFIG_FILE).exists() => {
+                Some(Config::read_table(Path::new(DEFAULT_CONFIG_FILE))?)
+            }
+            None => None,
+        };
+        Config::layered(args, layer)
+    }
+    // --rubric-json, the same keys as a config file
+    fn json_table(json: &str) -> Result<toml::Table, String> {
+        serde_json::from_str(json).map_err(|e| format!("bad --rubric-json: {}", e))
+    }
+    // The system config, then `layer` (the config file or --rubric-json), then flags
+    fn layered(args: &Args, layer: Option<toml::Table>) -> Result<Config, String> {
+        let mut table = toml::Table::new();
+        let system = Path::new(SYSTEM_CONFIG_FILE);
+        if system.exists() {
+            Config::merge(&mut table, Config::read_table(system)?);
+        }
+        if let Some(layer) = layer {
+            Config::merge(&mut table, layer);
+        }
+        let mut config: Config = table
+            .try_into()
+            .map_err(|e: toml::de::Error| format!("bad config: {}", e))?;
+        if args.auto_questions {
+            config.auto_questions = true;
+        }
+        if let Some(min) = args.skip_manual_if_below {
+            config.skip_manual_if_below = Some(min);
+        }
+        if config
+            .skip_manual_if_below
+            .is_some_and(|min| !(0.0..=100.0).contains(&min))
+        {
+            return Err("skip_manual_if_below has to be from 0 to 100".to_string());
+        }
+        if let Some(rate) = args.spelling_rate {
+            config
+                .categories
+                .iter_mut()
+                .find(|c| c.name == "spelling")
+                .ok_or("--spelling-rate needs a category named spelling")?
+                .rate = rate;
+        }
+        if let Some(tolerance) = args.caps_tolerance {
+            config
+                .categories
+                .iter_mut()
+                .find(|c| c.name == "capitalization")
+                .ok_or("--caps-tolerance needs a category named capitalization")?
+                .tolerance = tolerance;
+        }
+        if let Some(min) = args.min_transitions {
+            config.min_transitions = min;
+        }
+        if let Some(min) = args.min_paragraphs {
+            config.min_paragraphs = min;
+        }
+        if let Some(max) = args.max_sentence_words {
+            config.max_sentence_words = max;
+        }
+        if let Some(max) = args.max_long_sentences {
+            config.max_long_sentences = max;
+        }
+        if let Some(pattern) = &args.split_on {
+            config.split_on = Some(pattern.clone());
+        }
+        if let Some(min) = args.min_sentences_per_answer {
+            config.min_sentences_per_answer = min;
+        }
+        config
+            .forbidden_words
+            .extend(args.forbidden_words.iter().cloned());
+        if let Some(min) = args.min_links {
+            config.min_links = min;
+        }
+        if config.min_links == 0 {
+            return Err("min_links has to be at least 1".to_string());
+        }
+        if args.require_title {
+            config.require_title = true;
+        }
+        if let Some(strictness) = args.title_strictness {
+            config.title_strictness = strictness;
+        }
+        if let Some(pattern) = &args.title_pattern {
+            config.title_pattern = Some(pattern.clone());
+        }
+        if let Some(pattern) = &args.closing_pattern {
+            config.closing_pattern = Some(pattern.clone());
+        }
+        if args.closing_anywhere {
+            config.closing_anywhere = true;
+        }
+        if let Some(level) = args.min_grade_level {
+            config.min_grade_level = Some(level);
+        }
+        if let Some(level) = args.max_grade_level {
+            config.max_grade_level = Some(level);
+        }
+        if let (Some(min), Some(max)) = (config.min_grade_level, config.max_grade_level)
+            && min > max
+        {
+            return Err(format!(
+                "min_grade_level {} is above max_grade_level {}",
+                min, max
+            ));
+        }
+        if let Some(mix) = args.max_tense_mix {
+            config.max_tense_mix = Some(mix);
+        }
+        if let Some(share) = args.max_same_opener {
+            config.max_same_opener = Some(share);
+        }
+        if let Some(share) = args.max_paragraph_share {
+            config.max_paragraph_share = Some(share);
+        }
+        if let Some(density) = args.max_filler_density {
+            config.max_filler_density = Some(density);
+        }
+        if config
+            .max_filler_density
+            .is_some_and(|d| d.is_nan() || d < 0.0)
+        {
+            return Err("max_filler_density can't be negative".to_string());
+        }
+        if let Some(weight) = args.early_error_weight {
+            config.early_error_weight = Some(weight);
+        }
+        if let Some(decay) = args.early_error_decay {
+            config.early_error_decay = decay;
+        }
+        if config
+            .early_error_weight
+            .is_some_and(|w| w.is_nan() || w < 1.0)
+        {
+            return Err("early_error_weight has to be at least 1".to_string());
+        }
+        if args.require_complete_paragraphs {
+            config.require_complete_paragraphs = true;
+        }
+        if args.require_balanced_quotes {
+            config.require_balanced_quotes = true;
+        }
+        if let Some(voice) = args.voice {
+            config.voice = Some(voice);
+        }
+        if let Some(max) = args.max_first_person {
+            config.max_first_person = max;
+        }
+        if let Some(unevaluated) = args.unevaluated {
+            config.unevaluated = unevaluated;
+        }
+        if let Some(rounding) = args.rounding {
+            config.rounding = rounding;
+        }
+        if let Some(exemption) = args.list_exemption {
+            config.list_exemption = exemption;
+        }
+        if let Some(british) = args.british_spellings {
+            config.british_spellings = british;
+        }
+        if !args.spelling_ignore.is_empty() {
+            config.spelling_ignore = args.spelling_ignore.clone();
+        }
+        if args.strip_urls_before_linting {
+            config.strip_urls_before_linting = true;
+        }
+        if let Some(hyphenated) = args.hyphenated_words {
+            config.hyphenated_words = hyphenated;
+        }
+        if let Some(contractions) = args.contractions {
+            config.contractions = contractions;
+        }
+        if let Some(assignment) = &args.assignment {
+            config.assignment = Some(assignment.clone());
+        }
+        if let Some(due) = &args.due_date {
+            config.due_date = Some(due.clone());
+        }
+        if let Some(grader) = &args.grader {
+            config.grader = Some(grader.clone());
+        }
+        if !args.report_order.is_empty() {
+            config.report_order = args.report_order.clone();
+        }
+        if let Some(preset) = args.linters {
+            config.linters.preset = preset;
+        }
+        if !args.only_linters.is_empty() {
+            config.linters.only = args.only_linters.clone();
+        }
+        config.linters.disable.extend(args.disable.iter().cloned());
+        config.linters.check()?;
+        if let Some(pattern) = &config.title_pattern {
+            regex::Regex::new(pattern).map_err(|e| format!("bad title_pattern: {}", e))?;
+        }
+        if let Some(pattern) = &config.closing_pattern {
+            regex::Regex::new(pattern).map_err(|e| format!("bad closing_pattern: {}", e))?;
+        }
+        if let Some(marker) = &args.prompt_marker {
+            config.prompt_marker = Some(marker.clone());
+        }
+        if let Some(marker) = &config.prompt_marker {
+            regex::Regex::new(marker).map_err(|e| format!("bad prompt_marker: {}", e))?;
+        }
+        if let Some(pattern) = &config.split_on {
+            regex::Regex::new(pattern).map_err(|e| format!("bad split_on: {}", e))?;
+        }
+        if let Some(path) = &args.prompt_file {
+            config.prompt_file = Some(path.clone());
+        }
+        if let Some(path) = &config.prompt_file {
+            let prompt = std::fs::read_to_string(path)
+                .map_err(|e| format!("can't read prompt_file {}: {}", path.display(), e))?;
+            config.prompt = Some(prompt);
+        }
+        if let Some(path) = &args.answer_key {
+            config.answer_key = Some(path.clone());
+        }
+        if let Some(path) = &config.answer_key {
+            let text = std::fs::read_to_string(path)
+                .map_err(|e| format!("can't read answer_key {}: {}", path.display(), e))?;
+            config.expected_answers = KeyAnswer::parse_key(&text)
+                .map_err(|e| format!("answer_key {}: {}", path.display(), e))?;
+        }
+        if let Some(path) = &args.changed_only {
+            // read like the submissions, in their encoding and format
+            let previous = crate::input::Source::File(path.clone())
+                .read(crate::input::Reading::of(args))
+                .map_err(|e| format!("previous draft: {}", e))?;
+            config.previous = Some(previous.contents);
+        }
+        for (i, category) in config.categories.iter().enumerate() {
+            category.validate()?;
+            let earlier = &config.categories[..i];
+            if earlier.iter().any(|c| c.name == category.name) {
+                return Err(format!("category {} is defined twice", category.name));
+            }
+            if category.check != Check::Lints && earlier.iter().any(|c| c.check == category.check) {
+                return Err(format!("only one {:?} category is allowed", category.check));
+            }
+        }
+        for (kind, chain) in &config.lint_priority {
+            if !LINT_KINDS.iter().any(|k| k.to_string_key() == *kind) {
+                return Err(format!(
+                    "lint_priority: unknown lint kind {}, expected one of {}",
+                    kind,
+                    LINT_KINDS
+                        .iter()
+                        .map(|k| k.to_string_key())
+                        .collect::<Vec<_>>()
+                        .join(", ")
+                ));
+            }
+            if chain.is_empty() {
+                return Err(format!("lint_priority: {} lists no categories", kind));
+            }
+            // a name this config doesn't have is fine, the chain moves on to the next
+            if let Some(c) = config
+                .categories
+                .iter()
+                .find(|c| chain.contains(&c.name) && c.check != Check::Lints)
+            {
+                return Err(format!(
+                    "lint_priority: {} lists {}, which isn't a lints category",
+                    kind, c.name
+                ));
+            }
+        }
+        for (i, set) in config.keyword_sets.iter().enumerate() {
+            if set.name.trim().is_empty() {
+                return Err("a keyword set needs a name".to_string());
+            }
+            if set.keywords.iter().all(|k| k.trim().is_empty()) {
+                return Err(format!("keyword set {}: keywords is empty", set.name));
+            }
+            if set
+                .min
+                .is_some_and(|min| min == 0 || min > set.keywords.len())
+            {
+                return Err(format!(
+                    "keyword set {}: min has to be between 1 and {}",
+                    set.name,
+                    set.keywords.len()
+                ));
+            }
+            let taken = config.categories.iter().any(|c| c.name == set.name)
+                || config.keyword_sets[..i].iter().any(|s| s.name == set.name);
+            if taken {
+                return Err(format!(
+                    "keyword set {}: the name is already used",
+                    set.name
+                ));
+            }
+        }
+        for (i, section) in config.sections.iter().enumerate() {
+            if section.name.trim().is_empty() {
+                return Err("a section needs a name".to_string());
+            }
+            glob::Pattern::new(&section.files).map_err(|e| {
+                format!(
+                    "section {}: bad files {}: {}",
+                    section.name, section.files, e
+                )
+            })?;
+            if section.weight.is_nan() || section.weight < 0.0 {
+                return Err(format!(
+                    "section {}: weight can't be negative",
+                    section.name
+                ));
+            }
+            if config.sections[..i].iter().any(|s| s.name == section.name) {
+                return Err(format!(
+                    "section {}: the name is already used",
+                    section.name
+                ));
+            }
+        }
+        if !config.sections.is_empty()
+            && config.sections.iter().map(|s| s.weight).sum::<f32>() <= 0.0
+        {
+            return Err("sections: at least one needs a weight above 0".to_string());
+        }
+        let scored = config
+            .categories
+            .iter()
+            .filter(|c| c.severity == Severity::Fail);
+        if scored.map(|c| c.weight).sum::<f32>() <= 0.0 {
+            return Err("the weights of the scored categories add up to 0".to_string());
+        }
+        for category in &mut config.categories {
+            if category.description.is_empty() {
+                category.description = category.name.clone();
+            }
+        }
+        Ok(config)
+    }
+    pub fn criteria(&self) -> Vec<CriterionInfo> {
+        let mut criteria: Vec<CriterionInfo> = self
+            .categories
+            .iter()
+            .map(|c| CriterionInfo {
+                name: c.name.clone(),
+                description: c.description.clone(),
+                enabled: true,
+                weight: match c.severity {
+                    Severity::Fail => c.weight,
+                    Severity::Warn => 0.0,
+                },
+                settings: match c.check {
+                    Check::Link => {
+                        let mut settings = format!("domains: {}", self.link_domains.join(", "));
+                        if self.min_links > 1 {
+                            settings += &format!(", links needed: {}", self.min_links);
+                        }
+                        if !self.forbidden_link_domains.is_empty() {
+                            settings +=
+                                &format!(", forbidden: {}", self.forbidden_link_domains.join(", "));
+                        }
+                        settings
+                    }
+                    Check::Questions if self.auto_questions => {
+                        "estimated from the text".to_string()
+                    }
+                    Check::Questions => "asked at the prompt".to_string(),
+                    Check::Lints => {
+                        let mut settings = format!("lints: {}", c.lints.join(", "));
+                        if c.rate > 0.0 {
+                            settings += &format!(", mistakes allowed per word: {}", c.rate);
+                        }
+                        if c.tolerance > 0 {
+                            settings += &format!(", mistakes allowed anyway: {}", c.tolerance);
+                        }
+                        settings
+                    }
+                } + match c.severity {
+                    Severity::Fail => "",
+                    Severity::Warn => ", warning only",
+                },
+            })
+            .collect();
+        criteria.extend(self.extras().into_iter().map(|(_, info)| info));
+        let total: f32 = criteria
+            .iter()
+            .filter(|c| c.enabled)
+            .map(|c| c.weight)
+            .sum();
+        for c in &mut criteria {
+            c.weight = if c.enabled {
+                round_percent(100.0 * c.weight / total)
+            } else {
+                0.0
+            };
+        }
+        criteria
+    }
+    // Every optional criterion, enabled or not, with what --show-rubric says
+    // about it. Rubric::grade goes through the same list, so the preview
+    // can't drift from what gets graded
+    pub(crate) fn extras(&self) -> Vec<(Extra, CriterionInfo)> {
+        let info =
+            |name: &str, description: String, enabled: bool, settings: String| CriterionInfo {
+                name: name.to_string(),
+                description,
+                enabled,
+                weight: EXTRA_WEIGHT,
+                settings,
+            };
+        let mut extras = vec![
+            (
+                Extra::Paragraphs,
+                info(
+                    "paragraphs",
+                    format!("At least {} paragraphs", self.min_paragraphs),
+                    self.min_paragraphs > 0,
+                    format!("minimum: {}", self.min_paragraphs),
+                ),
+            ),
+            (
+                Extra::Transitions,
+                info(
+                    "transitions",
+                    format!("Uses at least {} transition words", self.min_transitions),
+                    self.min_transitions > 0,
+                    format!(
+                        "minimum: {}, out of {} words",
+                        self.min_transitions,
+                        self.transition_words.len()
+                    ),
+                ),
+            ),
+            (
+                Extra::SentenceLength,
+                info(
+                    "sentence_length",
+                    format!(
+                        "At most {} sentences over {} words",
+                        self.max_long_sentences, self.max_sentence_words
+                    ),
+                    self.max_sentence_words > 0,
+                    format!(
+                        "words per sentence: {}, long sentences allowed: {}",
+                        self.max_sentence_words, self.max_long_sentences
+                    ),
+                ),
+            ),
+            (
+                Extra::AnswerSentences,
+                info(
+                    "answer_sentences",
+                    format!(
+                        "Every answer has at least {} sentences",
+                        self.min_sentences_per_answer
+                    ),
+                    self.min_sentences_per_answer > 0,
+                    match &self.split_on {
+                        Some(pattern) => format!("answers start on lines matching {}", pattern),
+                        None => "the whole text is one answer".to_string(),
+                    },
+                ),
+            ),
+            (
+                Extra::ForbiddenWords,
+                info(
+                    "forbidden_words",
+                    "Doesn't use forbidden words".to_string(),
+                    !self.forbidden_words.is_empty(),
+                    format!("forbidden: {}", self.forbidden_words.join(", ")),
+                ),
+            ),
+            (
+                Extra::Title,
+                info(
+                    "title",
+                    "Starts with a title".to_string(),
+                    self.require_title,
+                    match &self.title_pattern {
+                        Some(pattern) => format!("pattern: {}", pattern),
+                        None => format!(
+                            "{:?}, at most {} words",
+                            self.title_strictness, self.title_max_words
+                        ),
+                    },
+                ),
+            ),
+            (
+                Extra::Closing,
+                info(
+                    "closing",
+                    self.closing_description().to_string(),
+                    self.closing_pattern.is_some(),
+                    format!(
+                        "pattern: {}",
+                        self.closing_pattern.as_deref().unwrap_or_default()
+                    ),
+                ),
+            ),
+            (
+                Extra::Readability,
+                info(
+                    "readability",
+                    self.grade_level_description(),
+                    self.min_grade_level.is_some() || self.max_grade_level.is_some(),
+                    "Flesch-Kincaid grade level, syllables are estimated".to_string(),
+                ),
+            ),
+            (
+                Extra::Tense,
+                info(
+                    "tense",
+                    "Sticks to one tense (experimental)".to_string(),
+                    self.max_tense_mix.is_some(),
+                    format!(
+                        "share of verbs allowed in the other tense: {}",
+                        self.max_tense_mix.unwrap_or_default()
+                    ),
+                ),
+            ),
+            (
+                Extra::Openers,
+                info(
+                    "openers",
+                    "Varies how sentences start (experimental)".to_string(),
+                    self.max_same_opener.is_some(),
+                    format!(
+                        "share of sentences allowed to start with the same word: {}",
+                        self.max_same_opener.unwrap_or_default()
+                    ),
+                ),
+            ),
+            (
+                Extra::ParagraphBalance,
+                info(
+                    "paragraph_balance",
+                    "Balanced paragraph lengths (experimental)".to_string(),
+                    self.max_paragraph_share.is_some(),
+                    format!(
+                        "share of the words allowed in the longest paragraph: {}",
+                        self.max_paragraph_share.unwrap_or_default()
+                    ),
+                ),
+            ),
+            (
+                Extra::Filler,
+                info(
+                    "filler",
+                    "Little generic filler (experimental heuristic)".to_string(),
+                    self.max_filler_density.is_some(),
+                    format!(
+                        "filler phrases allowed per 100 words: {}, phrases: {}",
+                        self.max_filler_density.unwrap_or_default(),
+                        self.filler_phrases.join(", ")
+                    ),
+                ),
+            ),
+            (
+                Extra::Voice,
+                info(
+                    "voice",
+                    self.voice.unwrap_or(Voice::Third).description().to_string(),
+                    self.voice.is_some(),
+                    match self.voice {
+                        Some(Voice::First) => "at least 1 first-person pronoun".to_string(),
+                        _ => format!("first-person pronouns allowed: {}", self.max_first_person),
+                    },
+                ),
+            ),
+            (
+                Extra::CompleteParagraphs,
+                info(
+                    "complete_paragraphs",
+                    "Every paragraph has a complete sentence (best effort)".to_string(),
+                    self.require_complete_paragraphs,
+                    "a subject and a finite verb, by harper's part of speech tags".to_string(),
+                ),
+            ),
+            (
+                Extra::Quotes,
+                info(
+                    "quotes",
+                    "Every quotation mark is closed".to_string(),
+                    self.require_balanced_quotes,
+                    "within the paragraph, apostrophes don't count".to_string(),
+                ),
+            ),
+            (
+                Extra::ConsistentSpelling,
+                info(
+                    "consistent_spelling",
+                    "Sticks to British or American spelling".to_string(),
+                    self.british_spellings == BritishSpellings::Consistent,
+                    "by harper's dictionary dialects".to_string(),
+                ),
+            ),
+        ];
+        extras.extend(self.keyword_sets.iter().enumerate().map(|(i, set)| {
+            (
+                Extra::Keywords(i),
+                info(
+                    &set.name,
+                    set.description(),
+                    true,
+                    format!(
+                        "keywords: {}, needed: {}",
+                        set.keywords.join(", "),
+                        set.needed()
+                    ),
+                ),
+            )
+        }));
+        extras.push((
+            Extra::AnswerKey,
+            info(
+                "answer_key",
+                "Has the answers in the answer key".to_string(),
+                !self.expected_answers.is_empty(),
+                format!(
+                    "expected: {}, partial credit",
+                    self.expected_answers
+                        .iter()
+                        .map(|a| a.label.as_str())
+                        .collect::<Vec<_>>()
+                        .join(", ")
+                ),
+            ),
+        ));
+        extras
+    }
+    // A word count under this config's hyphenated_words and contractions
+    pub(crate) fn count_words(&self, count: WordCount) -> usize {
+        let mut words = count.parts;
+        if self.hyphenated_words == HyphenatedWords::One {
+            words = words.saturating_sub(count.hyphen_joins);
+        }
+        if self.contractions == Contractions::Two {
+            words += count.contractions;
+        }
+        words
+    }
+    // How many times a mistake `position` of the way through the text (0 at
+    // the start, 1 at the end) counts, always 1 without early_error_weight
+    pub(crate) fn error_weight(&self, position: f32) -> f32 {
+        let Some(weight) = self.early_error_weight else {
+            return 1.0;
+        };
+        let rest = 1.0 - position.clamp(0.0, 1.0);
+        match self.early_error_decay {
+            Decay::Linear => 1.0 + (weight - 1.0) * rest,
+            Decay::Exponential => weight.powf(rest),
+        }
+    }
+    pub fn closing_description(&self) -> &'static str {
+        if self.closing_anywhere {
+            "Has a closing line"
+        } else {
+            "Ends with a closing line"
+        }
+    }
+    pub fn grade_level_description(&self) -> String {
+        match (self.min_grade_level, self.max_grade_level) {
+            (Some(min), Some(max)) => format!("Reads at grade level {} to {}", min, max),
+            (Some(min), None) => format!("Reads at grade level {} or above", min),
+            (None, Some(max)) => format!("Reads at grade level {} or below", max),
+            (None, None) => "Reads at the right grade level".to_string(),
+        }
+    }
+    pub fn show_rubric(&self) -> String {
+        let mut out = String::new();
+        for c in self.criteria() {
+            out += &format!(
+                "{}({}%): {} [{}]\n",
+                c.name,
+                c.weight,
+                c.description,
+                if c.enabled { "enabled" } else { "disabled" }
+            );
+            if !c.settings.is_empty() {
+                out += &format!("\t{}\n", c.settings);
+            }
+        }
+        let sections = self.sections_info();
+        if !sections.is_empty() {
+            out += "sections, each graded on the criteria above:\n";
+        }
+        for s in sections {
+            out += &format!("{}({}%): {}\n", s.name, s.weight, s.files);
+            out += &format!("\t{}\n", s.criteria.join(", "));
+        }
+        out
+    }
+    // Every section with its share of the total, empty without [[sections]]
+    fn sections_info(&self) -> Vec<SectionInfo> {
+        let total: f32 = self.sections.iter().map(|s| s.weight).sum();
+        let criteria: Vec<String> = self
+            .criteria()
+            .into_iter()
+            .filter(|c| c.enabled)
+            .map(|c| c.name)
+            .collect();
+        self.sections
+            .iter()
+            .map(|s| SectionInfo {
+                name: s.name.clone(),
+                files: s.files.clone(),
+                weight: round_percent(100.0 * s.weight / total),
+                criteria: criteria.clone(),
+            })
+            .collect()
+    }
+    // The category a mistake counts against: the first category of its kind's
+    // lint_priority chain that this config has, otherwise the first in report
+    // order that takes it, otherwise none and it's ignored
+    pub fn category_for(
+        &self,
+        kind: LintKind,
+        category: Option<LintCategory>,
+    ) -> Option<&Category> {
+        self.lint_priority
+            .get(&kind.to_string_key())
+            .and_then(|chain| {
+                chain
+                    .iter()
+                    .find_map(|name| self.categories.iter().find(|c| c.name == *name))
+            })
+            .or_else(|| self.categories.iter().find(|c| c.takes(kind, category)))
+    }
+    // One line naming every enabled criterion with its weight and settings
+    pub fn summary(&self) -> String {
+        let mut parts = vec![format!("dialect {:?}", DIALECT)];
+        for c in self.criteria().into_iter().filter(|c| c.enabled) {
+            if c.settings.is_empty() {
+                parts.push(format!("{} {}%", c.name, c.weight));
+            } else {
+                parts.push(format!("{} {}% ({})", c.name, c.weight, c.settings));
+            }
+        }
+        parts.join("; ")
+    }
+    // FNV-1a over the serialized settings, unlike DefaultHasher it won't change
+    // between Rust releases so old reports can still be matched to a config.
+    // The answer key, prompt and previous draft aren't serialized with the
+    // rest but change the scores just the same, so their contents count too
+    pub fn hash(&self) -> String {
+        let json =
+            serde_json::to_string(&(self, &self.expected_answers, &self.prompt, &self.previous))
+                .expect("failed to serialize config");
+        let mut hash: u64 = 0xcbf29ce484222325;
+        for byte in json.bytes() {
+            hash ^= byte as u64;
+            hash = hash.wrapping_mul(0x100000001b3);
+        }
+        format!("{:016x}", hash)
+    }
+    pub fn show_rubric_json(&self) -> String {
+        let preview = RubricPreview {
+            criteria: self.criteria(),
+            sections: self.sections_info(),
+            config: self,
+        };
+        serde_json::to_string_pretty(&preview).expect("failed to serialize rubric")
+    }
+}
@@ -0,0 +1,65 @@
+//! --db: every graded submission's scores in a SQLite file, one row per
+//! (file, assignment) so grading the same work again replaces it
+use crate::Rubric;
+use rusqlite::{Connection, params};
+use std::path::Path;
+
+const SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS submissions (
+    file TEXT NOT NULL,
+    assignment TEXT NOT NULL,
+    graded_at TEXT NOT NULL,
+    score REAL NOT NULL,
+    PRIMARY KEY (file, assignment)
+);
+CREATE TABLE IF NOT EXISTS criteria (
+    file TEXT NOT NULL,
+    assignment TEXT NOT NULL,
+    criterion TEXT NOT NULL,
+    -- percent of the criterion earned, NULL when it couldn't be evaluated
+    grade REAL,
+    weight REAL NOT NULL,
+    -- pass, fail or n/a
+    status TEXT,
+    PRIMARY KEY (file, assignment, criterion),
+    FOREIGN KEY (file, assignment) REFERENCES submissions (file, assignment) ON DELETE CASCADE
+);
+";
+
+// All of graded in one transaction, nothing is written if any of it fails
+pub fn store(path: &Path, assignment: &str, graded: &[(String, Rubric)]) -> Result<(), String> {
+    let fail = |e: rusqlite::Error| format!("can't write {}: {}", path.display(), e);
+    let mut db = Connection::open(path).map_err(fail)?;
+    db.execute_batch(SCHEMA).map_err(fail)?;
+    let graded_at = humantime::format_rfc3339_seconds(std::time::SystemTime::now()).to_string();
+    let tx = db.transaction().map_err(fail)?;
+    for (file, rubric) in graded {
+        tx.execute(
+            "INSERT INTO submissions (file, assignment, graded_at, score) VALUES (?1, ?2, ?3, ?4)
+             ON CONFLICT (file, assignment) DO UPDATE SET graded_at = ?3, score = ?4",
+            params![file, assignment, graded_at, rubric.score_percent()],
+        )
+        .map_err(fail)?;
+        // the criteria can change between runs, so start from none
+        tx.execute(
+            "DELETE FROM criteria WHERE file = ?1 AND assignment = ?2",
+            params![file, assignment],
+        )
+        .map_err(fail)?;
+        for c in &rubric.criteria {
+            tx.execute(
+                "INSERT INTO criteria (file, assignment, criterion, grade, weight, status) VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+                params![
+                    file,
+                    assignment,
+                    c.name,
+                    c.grade.val.map(|v| v * 100.0),
+                    rubric.weight_exact(c),
+                    c.grade.status().as_str()
+                ],
+            )
+            .map_err(fail)?;
+        }
+    }
+    tx.commit().map_err(fail)
+}
@@ -0,0 +1,56 @@
+//! --diff-score: how a batch's final scores move under another config
+use serde::Serialize;
+
+#[derive(Serialize)]
+pub struct Row {
+    pub file: String,
+    /// Final score in percent under the current config
+    pub before: f32,
+    /// Final score in percent under the --diff-score config
+    pub after: f32,
+}
+
+fn average(rows: &[Row], score: impl Fn(&Row) -> f32) -> f32 {
+    rows.iter().map(score).sum::<f32>() / rows.len().max(1) as f32
+}
+
+pub fn table(rows: &[Row]) -> String {
+    let width = rows.iter().map(|r| r.file.len()).max().unwrap_or(0).max(7);
+    let mut table = format!(
+        "{:width$}  {:>6}  {:>6}  {:>6}\n",
+        "file", "before", "after", "diff"
+    );
+    for row in rows {
+        table += &format!(
+            "{:width$}  {:>6}  {:>6}  {:>+6}\n",
+            row.file,
+            row.before,
+            row.after,
+            row.after - row.before
+        );
+    }
+    let before = average(rows, |r| r.before);
+    let after = average(rows, |r| r.after);
+    table += &format!(
+        "{:width$}  {:>6.1}  {:>6.1}  {:>+6.1}\n",
+        "average",
+        before,
+        after,
+        after - before
+    );
+    table
+}
+
+pub fn csv(rows: &[Row]) -> String {
+    let mut csv = "file,before,after,diff\n".to_string();
+    for row in rows {
+        csv += &format!(
+            "{},{},{},{}\n",
+            crate::csv_field(&row.file),
+            row.before,
+            row.after,
+            row.after - row.before
+        );
+    }
+    csv
+}
@@ -0,0 +1,496 @@
+//! Where submissions come from: files, directories of them, urls or the clipboard
+use crate::Args;
+use encoding_rs::Encoding;
+use rand::SeedableRng;
+use rand::rngs::StdRng;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+pub const DEFAULT_INPUT: &str = "input.txt";
+pub const IGNORE_FILE: &str = ".quickgradeignore";
+
+// What produced the text, some exports leave artifacts worth cleaning up first
+#[derive(clap::ValueEnum, Clone, Copy, Debug, Default, PartialEq)]
+pub enum InputFormat {
+    /// Graded as is
+    #[default]
+    Plain,
+    /// Google Docs "download as plain text"
+    Gdoc,
+}
+
+pub struct Submission {
+    /// Shown in batch output, the path, url or `clipboard`
+    pub name: String,
+    /// Where --format pdf writes the feedback sheet
+    pub pdf_path: PathBuf,
+    pub contents: String,
+}
+
+#[cfg(feature = "clipboard")]
+fn read_clipboard() -> Result<String, String> {
+    let mut clipboard =
+        arboard::Clipboard::new().map_err(|e| format!("can't open the clipboard: {}", e))?;
+    let text = clipboard.get_text().map_err(|e| match e {
+        arboard::Error::ContentNotAvailable => "the clipboard doesn't hold any text".to_string(),
+        e => format!("can't read the clipboard: {}", e),
+    })?;
+    if text.trim().is_empty() {
+        return Err("the clipboard is empty".to_string());
+    }
+    Ok(text)
+}
+#[cfg(not(feature = "clipboard"))]
+fn read_clipboard() -> Result<String, String> {
+    Err(
+        "quickgrade was built without clipboard support, rebuild with --features clipboard"
+            .to_string(),
+    )
+}
+
+// Gists and pastebins answer quickly, anything slower is probably stuck
+#[cfg(feature = "network")]
+const FETCH_TIMEOUT_SECS: u64 = 30;
+
+#[cfg(feature = "network")]
+fn fetch(url: &str) -> Result<String, String> {
+    let client = reqwest::blocking::Client::builder()
+        .timeout(std::time::Duration::from_secs(FETCH_TIMEOUT_SECS))
+        .build()
+        .map_err(|e| format!("can't fetch {}: {}", url, e))?;
+    let response = client.get(url).send().map_err(|e| {
+        if e.is_timeout() {
+            return format!(
+                "can't fetch {}: no answer after {}s",
+                url, FETCH_TIMEOUT_SECS
+            );
+        }
+        // reqwest's own message leaves out why, e.g. the dns or tls failure
+        let mut message = format!("can't fetch {}: {}", url, e);
+        let mut source = std::error::Error::source(&e);
+        while let Some(cause) = source {
+            message += &format!(": {}", cause);
+            source = cause.source();
+        }
+        message
+    })?;
+    if !response.status().is_success() {
+        return Err(format!(
+            "can't fetch {}: the server answered {}",
+            url,
+            response.status()
+        ));
+    }
+    response
+        .text()
+        .map_err(|e| format!("can't read {}: {}", url, e))
+}
+#[cfg(not(feature = "network"))]
+fn fetch(url: &str) -> Result<String, String> {
+    Err(format!(
+        "can't fetch {}: quickgrade was built without network support, rebuild with --features network",
+        url
+    ))
+}
+
+fn is_url(path: &Path) -> bool {
+    path.to_str()
+        .is_some_and(|p| p.starts_with("http://") || p.starts_with("https://"))
+}
+
+pub fn read_file(path: &Path) -> Result<String, String> {
+    let mut f =
+        std::fs::File::open(path).map_err(|e| format!("can't open {}: {}", path.display(), e))?;
+    let mut contents = String::new();
+    f.read_to_string(&mut contents)
+        .map_err(|e| format!("can't read {}: {}", path.display(), e))?;
+    Ok(contents)
+}
+
+/// How the bytes of a submission become text, from --input-format and
+/// --input-encoding
+#[derive(Clone, Copy)]
+pub struct Reading {
+    pub format: InputFormat,
+    pub encoding: Option<&'static Encoding>,
+}
+impl Reading {
+    pub fn of(args: &Args) -> Reading {
+        Reading {
+            format: args.input_format,
+            encoding: args.input_encoding,
+        }
+    }
+}
+
+// --input-encoding takes the WHATWG labels: latin1, windows-1252, utf-16le...
+pub fn parse_encoding(label: &str) -> Result<&'static Encoding, String> {
+    Encoding::for_label(label.trim().as_bytes()).ok_or_else(|| {
+        format!(
+            "unsupported encoding {}, try utf-8, utf-16le, utf-16be, latin1 or windows-1252",
+            label
+        )
+    })
+}
+
+// Every submission is read through here whatever its source: without
+// `encoding` UTF-8 (anything else is an error) unless a byte order mark says
+// UTF-16, with `encoding` that one unless a byte order mark says otherwise.
+// The text comes out without a byte order mark, with \n line endings,
+// sanitized. Returns it and how many control characters were touched
+pub fn read_text(
+    mut reader: impl Read,
+    encoding: Option<&'static Encoding>,
+) -> std::io::Result<(String, usize)> {
+    let mut bytes = Vec::new();
+    reader.read_to_end(&mut bytes)?;
+    let encoding = Encoding::for_bom(&bytes)
+        .map(|(encoding, _)| encoding)
+        .or(encoding)
+        .unwrap_or(encoding_rs::UTF_8);
+    let contents = if encoding == encoding_rs::UTF_8 {
+        String::from_utf8(bytes).map_err(|_| {
+            std::io::Error::new(
+                std::io::ErrorKind::InvalidData,
+                "not valid UTF-8, see --input-encoding",
+            )
+        })?
+    } else {
+        let (contents, malformed) = encoding.decode_with_bom_removal(&bytes);
+        if malformed {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::InvalidData,
+                format!("not valid {}", encoding.name()),
+            ));
+        }
+        log::debug!("decoded from {}", encoding.name());
+        contents.into_owned()
+    };
+    let contents = normalize_line_endings(contents.trim_start_matches('\u{feff}'));
+    Ok(sanitize(&contents))
+}
+
+// \r\n and old Mac style lone \r both become \n, so paragraphs and the
+// line numbers in reports come out the same whatever mix a file has
+fn normalize_line_endings(contents: &str) -> String {
+    contents.replace("\r\n", "\n").replace('\r', "\n")
+}
+
+// Control characters other than newlines and tabs confuse harper and garble
+// reports, the whitespace ones (vertical tab, form feed) become spaces and
+// the rest (null bytes, escapes) are dropped. Returns how many were touched
+pub fn sanitize(contents: &str) -> (String, usize) {
+    let mut removed = 0;
+    let clean = contents
+        .chars()
+        .filter_map(|c| match c {
+            '\n' | '\t' | '\r' => Some(c),
+            c if c.is_control() => {
+                removed += 1;
+                c.is_whitespace().then_some(' ')
+            }
+            c => Some(c),
+        })
+        .collect();
+    (clean, removed)
+}
+
+fn excluded(path: &Path, patterns: &[glob::Pattern]) -> bool {
+    path.file_name()
+        .and_then(|name| name.to_str())
+        .is_some_and(|name| patterns.iter().any(|p| p.matches(name)))
+}
+
+// The globs in a directory's .quickgradeignore, one per line, blank lines and
+// lines starting with # skipped
+fn ignore_patterns(dir: &Path) -> Result<Vec<glob::Pattern>, String> {
+    let path = dir.join(IGNORE_FILE);
+    if !path.is_file() {
+        return Ok(Vec::new());
+    }
+    read_file(&path)?
+        .lines()
+        .map(|line| line.trim())
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .map(|line| {
+            glob::Pattern::new(line)
+                .map_err(|e| format!("bad pattern {} in {}: {}", line, path.display(), e))
+        })
+        .collect()
+}
+
+// Undoes what a Google Docs plain text export adds: the byte order mark,
+// \r\n line endings, trailing spaces (non-breaking ones too), lines of
+// = - or _ underlining a heading, and the blank lines it doubles up between
+// paragraphs, any run of them becomes one
+pub fn normalize_gdoc(contents: &str) -> String {
+    let contents = contents
+        .trim_start_matches('\u{feff}')
+        .replace("\r\n", "\n");
+    let mut lines: Vec<&str> = Vec::new();
+    for line in contents.lines() {
+        let line = line.trim_end_matches([' ', '\t', '\u{a0}']);
+        let underline = line.chars().count() >= 3
+            && line.chars().all(|c| c == line.chars().next().unwrap())
+            && line.starts_with(['=', '-', '_']);
+        if underline && lines.last().is_some_and(|l| !l.is_empty()) {
+            continue;
+        }
+        if line.is_empty() && lines.last().is_none_or(|l| l.is_empty()) {
+            continue;
+        }
+        lines.push(line);
+    }
+    while lines.last().is_some_and(|l| l.is_empty()) {
+        lines.pop();
+    }
+    let mut out = lines.join("\n");
+    out.push('\n');
+    out
+}
+
+// The files named on the command line with directories expanded to the .txt
+// files directly inside them, or input.txt when nothing was named. Files whose
+// name matches an --exclude glob, or one in their directory's
+// .quickgradeignore, are left out
+pub fn input_files(args: &Args) -> Result<Vec<PathBuf>, String> {
+    if args.inputs.is_empty() {
+        return Ok(vec![PathBuf::from(DEFAULT_INPUT)]);
+    }
+    let exclude = args
+        .exclude
+        .iter()
+        .map(|p| glob::Pattern::new(p).map_err(|e| format!("bad --exclude {}: {}", p, e)))
+        .collect::<Result<Vec<_>, _>>()?;
+    let mut files = Vec::new();
+    let mut skipped = 0;
+    for path in &args.inputs {
+        if path.is_dir() {
+            let entries = std::fs::read_dir(path)
+                .map_err(|e| format!("can't read {}: {}", path.display(), e))?;
+            let ignored = ignore_patterns(path)?;
+            let mut found: Vec<PathBuf> = entries
+                .filter_map(|e| e.ok())
+                .map(|e| e.path())
+                .filter(|p| p.is_file() && p.extension().is_some_and(|e| e == "txt"))
+                .collect();
+            found.sort();
+            let before = found.len();
+            found.retain(|p| !excluded(p, &exclude) && !excluded(p, &ignored));
+            skipped += before - found.len();
+            files.extend(found);
+        } else if !is_url(path) && excluded(path, &exclude) {
+            skipped += 1;
+        } else {
+            files.push(path.clone());
+        }
+    }
+    if skipped > 0 {
+        crate::report::note(&format!("skipped {} excluded files", skipped));
+    }
+    if let Some(since) = args.since {
+        let before = files.len();
+        files.retain(|path| is_url(path) || modified_since(path, since));
+        crate::report::note(&format!(
+            "{} of {} files modified since {}",
+            files.len(),
+            before,
+            humantime::format_rfc3339_seconds(since)
+        ));
+    }
+    if let Some(size) = args.sample {
+        files = sample(files, size, args.seed);
+    }
+    Ok(files)
+}
+
+// --sample: `size` of the files picked at random, kept in the order they
+// were found. The seed is always printed so the same sample can be drawn
+// again with --seed
+fn sample(files: Vec<PathBuf>, size: usize, seed: Option<u64>) -> Vec<PathBuf> {
+    if size >= files.len() {
+        crate::report::note(&format!(
+            "sample of {} covers all {} files",
+            size,
+            files.len()
+        ));
+        return files;
+    }
+    let seed = seed.unwrap_or_else(rand::random);
+    let mut rng = StdRng::seed_from_u64(seed);
+    let mut picked = rand::seq::index::sample(&mut rng, files.len(), size).into_vec();
+    picked.sort();
+    crate::report::note(&format!(
+        "sampled {} of {} files (--seed {}):",
+        size,
+        files.len(),
+        seed
+    ));
+    let chosen: Vec<PathBuf> = files
+        .into_iter()
+        .enumerate()
+        .filter(|(i, _)| picked.binary_search(i).is_ok())
+        .map(|(_, path)| path)
+        .collect();
+    for path in &chosen {
+        crate::report::note(&format!("  {}", path.display()));
+    }
+    chosen
+}
+
+// Named pipes block on open until a writer shows up and read until it
+// closes, so each open is one draft
+#[cfg(unix)]
+pub fn is_fifo(path: &Path) -> bool {
+    use std::os::unix::fs::FileTypeExt;
+    std::fs::metadata(path).is_ok_and(|m| m.file_type().is_fifo())
+}
+#[cfg(not(unix))]
+pub fn is_fifo(_: &Path) -> bool {
+    false
+}
+
+// Files whose modification time can't be read are kept, grading one too many
+// beats silently dropping a submission
+fn modified_since(path: &Path, since: SystemTime) -> bool {
+    std::fs::metadata(path)
+        .and_then(|m| m.modified())
+        .map_or(true, |modified| modified >= since)
+}
+
+// --since takes a date (2024-05-01) or a date and time (2024-05-01 13:00:00
+// or 2024-05-01T13:00:00Z), both in UTC, or a duration back from now (1h,
+// 2days, 1w). Other UTC offsets aren't supported
+pub fn parse_since(text: &str) -> Result<SystemTime, String> {
+    let text = text.trim();
+    if let Ok(ago) = humantime::parse_duration(text) {
+        return SystemTime::now()
+            .checked_sub(ago)
+            .ok_or_else(|| format!("{} ago is before the epoch", text));
+    }
+    let timestamp = if text.len() == 10 {
+        format!("{} 00:00:00", text)
+    } else {
+        text.to_string()
+    };
+    humantime::parse_rfc3339_weak(&timestamp).map_err(|_| {
+        format!(
+            "can't read '{}' as a UTC date (2024-05-01), date and time (2024-05-01 13:00:00) or duration (1h, 2days)",
+            text
+        )
+    })
+}
+
+// A file named in --baseline or --seed-answers matches the graded path or
+// just its file name
+pub fn names_file(entry: &str, name: &str) -> bool {
+    entry == name || Path::new(name).file_name().is_some_and(|f| f == entry)
+}
+
+// More than one submission, each report gets labeled with its file
+pub fn is_batch(args: &Args) -> bool {
+    !args.clipboard
+        && !args.aggregate
+        && (args.inputs.len() > 1 || args.inputs.iter().any(|p| p.is_dir()))
+}
+
+pub enum Source {
+    Clipboard,
+    File(PathBuf),
+    Url(String),
+    /// --aggregate: the sources one after another, a blank line between them
+    Aggregate(Vec<Source>),
+}
+impl Source {
+    pub fn name(&self) -> String {
+        match self {
+            Source::Clipboard => "clipboard".to_string(),
+            Source::File(path) => path.display().to_string(),
+            Source::Url(url) => url.clone(),
+            Source::Aggregate(sources) => sources
+                .iter()
+                .map(Source::name)
+                .collect::<Vec<_>>()
+                .join(" + "),
+        }
+    }
+    pub fn read(&self, reading: Reading) -> Result<Submission, String> {
+        let start = std::time::Instant::now();
+        // the clipboard hands over text, there's nothing left to decode
+        let (pdf_path, reader, encoding): (PathBuf, Box<dyn Read>, _) = match self {
+            Source::Clipboard => (
+                PathBuf::from("clipboard.pdf"),
+                Box::new(std::io::Cursor::new(read_clipboard()?)),
+                None,
+            ),
+            Source::File(path) => (
+                path.with_extension("pdf"),
+                Box::new(
+                    std::fs::File::open(path)
+                        .map_err(|e| format!("can't open {}: {}", path.display(), e))?,
+                ),
+                reading.encoding,
+            ),
+            // the sheet goes in the working directory, named after the last part of the url
+            Source::Url(url) => (
+                url.trim_end_matches('/')
+                    .rsplit('/')
+                    .next()
+                    .filter(|last| !last.is_empty() && !last.contains(':'))
+                    .map(|last| Path::new(last).with_extension("pdf"))
+                    .unwrap_or_else(|| PathBuf::from("download.pdf")),
+                Box::new(std::io::Cursor::new(fetch(url)?)),
+                reading.encoding,
+            ),
+            // each read in full on its own, the sheet goes in the working directory
+            Source::Aggregate(sources) => {
+                let parts = sources
+                    .iter()
+                    .map(|s| s.read(reading).map(|submission| submission.contents))
+                    .collect::<Result<Vec<_>, _>>()?;
+                return Ok(Submission {
+                    name: self.name(),
+                    pdf_path: PathBuf::from("aggregate.pdf"),
+                    contents: parts.join("\n\n"),
+                });
+            }
+        };
+        let name = self.name();
+        let (contents, removed) =
+            read_text(reader, encoding).map_err(|e| format!("can't read {}: {}", name, e))?;
+        if removed > 0 {
+            crate::report::warning(
+                "input",
+                &format!("{}: removed {} control characters", name, removed),
+            );
+        }
+        log::debug!("read {}: {} chars", name, contents.chars().count());
+        let mut submission = Submission {
+            name,
+            pdf_path,
+            contents,
+        };
+        if reading.format == InputFormat::Gdoc {
+            submission.contents = normalize_gdoc(&submission.contents);
+        }
+        crate::profile::record("read", start.elapsed());
+        Ok(submission)
+    }
+}
+
+pub fn sources(args: &Args) -> Result<Vec<Source>, String> {
+    if args.clipboard {
+        return Ok(vec![Source::Clipboard]);
+    }
+    Ok(input_files(args)?
+        .into_iter()
+        .map(|path| {
+            if is_url(&path) {
+                Source::Url(path.display().to_string())
+            } else {
+                Source::File(path)
+            }
+        })
+        .collect())
+}
@@ -0,0 +1,108 @@
+//! --answer-key: the answers a factual short-answer assignment expects, an
+//! `answer_key` criterion for what's right on top of how it's written
+use crate::Rubric;
+use regex::Regex;
+use serde::Serialize;
+
+/// How an expected answer is found in the text
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub enum Expected {
+    /// The words as written, whole words ignoring case
+    Phrase(String),
+    /// Any number in the text within `tolerance` of `value`
+    Number { value: f64, tolerance: f64 },
+    /// At least one of the phrases
+    AnyOf(Vec<String>),
+}
+
+/// One line of an answer key
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct KeyAnswer {
+    /// The line as written, minus its rule, what the report calls it
+    pub label: String,
+    pub expected: Expected,
+}
+
+impl KeyAnswer {
+    /// An answer key, one expected answer per line: a phrase as is (or after
+    /// `phrase:`), `number: 9.81 +- 0.05` or `any: mitochondria | powerhouse`.
+    /// Blank lines and lines starting with # are skipped
+    pub fn parse_key(text: &str) -> Result<Vec<KeyAnswer>, String> {
+        let mut answers = Vec::new();
+        for (i, line) in text.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let (rule, rest) = match line.split_once(':') {
+                Some((rule, rest)) if ["phrase", "number", "any"].contains(&rule.trim()) => {
+                    (rule.trim(), rest.trim())
+                }
+                _ => ("phrase", line),
+            };
+            let expected = match rule {
+                "number" => {
+                    KeyAnswer::parse_number(rest).map_err(|e| format!("line {}: {}", i + 1, e))?
+                }
+                "any" => Expected::AnyOf(
+                    rest.split('|')
+                        .map(|p| p.trim().to_string())
+                        .filter(|p| !p.is_empty())
+                        .collect(),
+                ),
+                _ => Expected::Phrase(rest.to_string()),
+            };
+            if matches!(&expected, Expected::Phrase(p) if p.is_empty())
+                || matches!(&expected, Expected::AnyOf(ps) if ps.is_empty())
+            {
+                return Err(format!("line {}: '{}' expects nothing", i + 1, line));
+            }
+            answers.push(KeyAnswer {
+                label: rest.to_string(),
+                expected,
+            });
+        }
+        if answers.is_empty() {
+            return Err("no expected answers".to_string());
+        }
+        Ok(answers)
+    }
+    // `9.81`, `9.81 +- 0.05` or `9.81 ± 0.05`
+    fn parse_number(text: &str) -> Result<Expected, String> {
+        let (value, tolerance) = match text.split_once("+-").or_else(|| text.split_once('±')) {
+            Some((value, tolerance)) => (value.trim(), tolerance.trim()),
+            None => (text, "0"),
+        };
+        let value: f64 = value
+            .parse()
+            .map_err(|_| format!("'{}' isn't a number", value))?;
+        let tolerance: f64 = tolerance
+            .parse()
+            .ok()
+            .filter(|t: &f64| *t >= 0.0)
+            .ok_or_else(|| format!("'{}' isn't a tolerance", tolerance))?;
+        Ok(Expected::Number { value, tolerance })
+    }
+    /// Whether the text has it
+    pub fn found_in(&self, contents: &str) -> bool {
+        match &self.expected {
+            Expected::Phrase(phrase) => {
+                !Rubric::phrase_hits(contents, std::slice::from_ref(phrase)).is_empty()
+            }
+            Expected::AnyOf(phrases) => !Rubric::phrase_hits(contents, phrases).is_empty(),
+            Expected::Number { value, tolerance } => numbers(contents)
+                .iter()
+                // a little slack so 0.1 + 0.2 style rounding doesn't miss
+                .any(|n| (n - value).abs() <= tolerance + 1e-9),
+        }
+    }
+}
+
+// Every number in the text, thousands separators dropped
+fn numbers(contents: &str) -> Vec<f64> {
+    let regex = Regex::new(r"-?\d[\d,]*(?:\.\d+)?").unwrap();
+    regex
+        .find_iter(contents)
+        .filter_map(|m| m.as_str().replace(',', "").parse().ok())
+        .collect()
+}
@@ -0,0 +1,3378 @@
+//! The grading engine, `grade_text` and `categorize` are useful on their
+//! own, and the command line tool in `run`
+use clap::Parser;
+use harper_brill::UPOS;
+use harper_core::Dialect;
+use harper_core::Document;
+use harper_core::Span;
+use harper_core::Token;
+use harper_core::TokenKind;
+use harper_core::TokenStringExt;
+use harper_core::linting::*;
+use harper_core::spell::{Dictionary, FstDictionary};
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::io;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use unicode_normalization::UnicodeNormalization;
+use unicode_segmentation::UnicodeSegmentation;
+
+mod answers;
+mod baseline;
+mod changes;
+mod comments;
+mod config;
+#[cfg(feature = "sqlite")]
+mod db;
+mod diff;
+mod input;
+mod key;
+#[cfg(feature = "pdf")]
+mod pdf;
+mod profile;
+mod prompt;
+mod report;
+mod sections;
+#[cfg(feature = "server")]
+mod server;
+mod sidecar;
+mod template;
+
+use answers::Answer;
+pub use config::{
+    BritishSpellings, Config, Contractions, HyphenatedWords, KeywordSet, LINTERS, LinterPreset,
+    Linters, ListExemption, Rounding, TitleStrictness, TokenClass, Voice,
+};
+use config::{Category, Check, Extra, Severity, Unevaluated};
+pub use key::{Expected, KeyAnswer};
+use sections::SectionGrade;
+pub use sidecar::Sidecar;
+
+/// The mistake categories the default rubric grades on
+#[derive(Default, Debug, Copy, Clone, PartialEq, Serialize, Deserialize, clap::ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum LintCategory {
+    Punctuation,
+    #[default]
+    Spelling,
+    Capitalization,
+}
+
+/// The category a harper lint kind counts toward, `None` for kinds quickgrade
+/// doesn't grade by default (grammar, style, word choice and so on).
+///
+/// - Spelling: `Spelling`, `Typo`, `BoundaryError`, `Eggcorn`, `Malapropism`
+/// - Punctuation: `Punctuation`
+/// - Capitalization: `Capitalization`
+pub fn categorize(kind: LintKind) -> Option<LintCategory> {
+    match kind {
+        LintKind::BoundaryError => Some(LintCategory::Spelling),
+        LintKind::Capitalization => Some(LintCategory::Capitalization),
+        LintKind::Eggcorn => Some(LintCategory::Spelling),
+        LintKind::Malapropism => Some(LintCategory::Spelling),
+        LintKind::Punctuation => Some(LintCategory::Punctuation),
+        LintKind::Spelling => Some(LintCategory::Spelling),
+        LintKind::Typo => Some(LintCategory::Spelling),
+        _ => None,
+    }
+}
+
+/// Grades a submission without ever prompting: the questions criterion is
+/// estimated under `auto_questions` and left unevaluated otherwise
+pub fn grade_text(contents: &str, config: &Config) -> Rubric {
+    let (contents, stripped) = prompt::strip(clean(contents.to_string()), config);
+    let lints = LintRun::new(&contents, config);
+    let mut rubric = Rubric::grade(&contents, config, &lints, None);
+    rubric.prompt_stripped = stripped;
+    rubric
+}
+
+/// Grades a sectioned assignment (see `Config::sections`) from its files' names
+/// and text, without prompting like `grade_text`. Each section is graded on its
+/// own, files matching the same one as one text, and the result has a criterion
+/// per section worth its weight. Files matching no section are left out
+pub fn grade_sections(files: &[(&str, &str)], config: &Config) -> Rubric {
+    let names: Vec<String> = files.iter().map(|(name, _)| name.to_string()).collect();
+    let (assigned, _) = sections::assign(&config.sections, &names);
+    let graded = config
+        .sections
+        .iter()
+        .zip(assigned)
+        .map(|(section, matched)| {
+            let rubric = (!matched.is_empty()).then(|| {
+                let parts: Vec<&str> = matched.iter().map(|&i| files[i].1).collect();
+                grade_text(&parts.join("\n\n"), config)
+            });
+            let matched = matched.iter().map(|&i| names[i].clone()).collect();
+            SectionGrade::new(section, matched, rubric)
+        })
+        .collect();
+    Rubric::combine_sections(config, graded)
+}
+
+// Everything harper found, which doesn't depend on the rubric so one run can
+// be scored under several configs
+#[derive(Debug, Clone, Default)]
+struct LintRun {
+    errors: Vec<LintError>,
+    words: WordCount,
+    /// Words only American English spells that way, like "color"
+    american: Vec<String>,
+    /// Why harper panicked, every lints category fails then
+    failure: Option<String>,
+    /// Rules that panicked on this text, the other rules' lints still count
+    failed_rules: Vec<String>,
+    /// Taken out under --review, they don't count anywhere
+    dismissed: Vec<LintError>,
+    /// How long harper took
+    elapsed: Duration,
+}
+type LintHandle = std::thread::JoinHandle<LintRun>;
+impl LintRun {
+    // The config's linters run, with strip_urls_before_linting harper sees
+    // links as blanks (see mask_links) and with a previous draft only the
+    // lints on lines changed since it are kept. Nothing else in it matters here
+    fn spawn(contents: &str, config: &Config) -> LintHandle {
+        let contents = contents.to_string();
+        let strip_urls = config.strip_urls_before_linting;
+        let rules = config.linters.rules();
+        let previous = config.previous.clone();
+        std::thread::spawn(move || {
+            let start = Instant::now();
+            let mut run = bucket_lints(&contents, strip_urls, &rules);
+            if let Some(previous) = previous {
+                let changed = profile::time("lint;changed_lines", || {
+                    changes::changed_lines(&clean(previous), &contents)
+                });
+                let before = run.errors.len();
+                run.errors
+                    .retain(|e| changed.get(e.line - 1).copied().unwrap_or(true));
+                log::debug!(
+                    "{} of {} lints on unchanged lines",
+                    before - run.errors.len(),
+                    before
+                );
+            }
+            run.elapsed = start.elapsed();
+            profile::record("lint", run.elapsed);
+            run
+        })
+    }
+    fn join(handle: LintHandle) -> LintRun {
+        match handle.join() {
+            Ok(run) => run,
+            Err(panic) => {
+                let reason = panic_reason(panic.as_ref());
+                report::warning(
+                    "lint",
+                    &format!("linting failed, failing the lint criteria: {}", reason),
+                );
+                LintRun {
+                    failure: Some(reason),
+                    ..Default::default()
+                }
+            }
+        }
+    }
+    fn new(contents: &str, config: &Config) -> LintRun {
+        LintRun::join(LintRun::spawn(contents, config))
+    }
+    // --review: lists the mistakes some category would count and drops the
+    // ones the grader calls false positives
+    fn review(&mut self, config: &Config) {
+        let counted: Vec<usize> = (0..self.errors.len())
+            .filter(|&i| {
+                let e = &self.errors[i];
+                config.category_for(e.kind, e.category).is_some()
+            })
+            .collect();
+        if counted.is_empty() {
+            return;
+        }
+        for (n, &i) in counted.iter().enumerate() {
+            let e = &self.errors[i];
+            eprintln!("[{}] '{}': {}", n + 1, e.text, e.message);
+        }
+        eprintln!("Numbers of the false positives to dismiss (e.g. 1 3), empty for none:");
+        let mut dismissed = LintRun::read_dismissals(io::stdin().lock(), &counted);
+        dismissed.sort();
+        dismissed.dedup();
+        for i in dismissed.into_iter().rev() {
+            self.dismissed.push(self.errors.remove(i));
+        }
+        self.dismissed.reverse();
+    }
+    // The picked indexes into errors, none when stdin ends or there is no
+    // valid answer in PROMPT_ATTEMPTS tries
+    fn read_dismissals(mut input: impl io::BufRead, counted: &[usize]) -> Vec<usize> {
+        for _ in 0..PROMPT_ATTEMPTS {
+            let mut line = String::new();
+            if input.read_line(&mut line).unwrap_or(0) == 0 {
+                log::info!("no answer to the review prompt, dismissing nothing");
+                return Vec::new();
+            }
+            let picked: Result<Vec<usize>, _> = line
+                .split([' ', ','])
+                .filter(|n| !n.trim().is_empty())
+                .map(|n| n.trim().parse::<usize>())
+                .collect();
+            match picked {
+                Ok(picked) if picked.iter().all(|n| (1..=counted.len()).contains(n)) => {
+                    return picked.into_iter().map(|n| counted[n - 1]).collect();
+                }
+                _ => eprintln!("Please give numbers from 1 to {}", counted.len()),
+            }
+        }
+        report::warning(
+            "input",
+            &format!(
+                "no valid answer to the review prompt in {} tries, dismissing nothing",
+                PROMPT_ATTEMPTS
+            ),
+        );
+        Vec::new()
+    }
+}
+
+// Debug builds only: the rule named in it panics, how the tests check that a
+// failing rule doesn't take the others down
+const PANIC_RULE: &str = "QUICKGRADE_PANIC_RULE";
+
+fn panic_reason(panic: &(dyn std::any::Any + Send)) -> String {
+    panic
+        .downcast_ref::<&str>()
+        .map(|s| s.to_string())
+        .or_else(|| panic.downcast_ref::<String>().cloned())
+        .unwrap_or_else(|| "unknown panic".to_string())
+}
+
+// NFC first, so "e" and a combining acute lint the same as "é"
+fn clean(contents: String) -> String {
+    profile::time("clean", || {
+        contents.nfc().filter(|c| *c != '’').collect() // that char panics lord knows why
+    })
+}
+
+// Serialized as its fraction so reports don't leak the Option, flattened into
+// the criterion with its status so a criterion that wasn't evaluated reads back.
+// `grade` (0 to 1) and `status` (pass, fail or n/a) are part of the JSON
+// output, don't rename them
+#[derive(Copy, Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(into = "GradeFields", from = "GradeFields")]
+struct Grade {
+    /// Fraction of the criterion's points earned, 0 to 1
+    val: Option<f32>,
+}
+impl Grade {
+    // Full marks
+    fn get(&self) -> bool {
+        self.perc() >= 1.0
+    }
+    fn status(&self) -> Status {
+        match self.val {
+            None => Status::NotEvaluated,
+            Some(_) if self.get() => Status::Pass,
+            Some(_) => Status::Fail,
+        }
+    }
+    fn perc(&self) -> f32 {
+        self.val.unwrap_or_default()
+    }
+    fn empty() -> Grade {
+        Grade { val: None }
+    }
+    fn new(v: bool) -> Grade {
+        Grade {
+            val: Some(if v { 1.0 } else { 0.0 }),
+        }
+    }
+    // A 0-100 score given by the grader
+    fn from_percentage(percent: f32) -> Grade {
+        Grade {
+            val: Some(percent.clamp(0.0, 100.0) / 100.0),
+        }
+    }
+    // Partial credit, found out of required capped at full marks
+    fn from_ratio(found: usize, required: usize) -> Grade {
+        Grade {
+            val: Some((found as f32 / required.max(1) as f32).min(1.0)),
+        }
+    }
+}
+
+// Full marks, anything less (the grade says how much) or not evaluated, its
+// grade is then 0
+#[derive(Copy, Clone, Debug, PartialEq, Serialize, Deserialize)]
+enum Status {
+    #[serde(rename = "pass")]
+    Pass,
+    #[serde(rename = "fail")]
+    Fail,
+    #[serde(rename = "n/a")]
+    NotEvaluated,
+}
+impl Status {
+    #[cfg(feature = "sqlite")]
+    fn as_str(&self) -> &'static str {
+        match self {
+            Status::Pass => "pass",
+            Status::Fail => "fail",
+            Status::NotEvaluated => "n/a",
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct GradeFields {
+    grade: f32,
+    status: Status,
+}
+impl From<Grade> for GradeFields {
+    fn from(grade: Grade) -> GradeFields {
+        GradeFields {
+            grade: grade.perc(),
+            status: grade.status(),
+        }
+    }
+}
+impl From<GradeFields> for Grade {
+    fn from(fields: GradeFields) -> Grade {
+        Grade {
+            val: (fields.status != Status::NotEvaluated).then_some(fields.grade.clamp(0.0, 1.0)),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct LintError {
+    /// None for kinds outside the three categories, a config category can still take them
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    category: Option<LintCategory>,
+    kind: LintKind,
+    /// Name of the rubric category it counted against
+    #[serde(default)]
+    criterion: String,
+    /// The offending text
+    text: String,
+    message: String,
+    /// Found on a list item, see `list_exemption`
+    #[serde(skip)]
+    list_item: bool,
+    /// Where it starts, from 0 at the start of the text to 1 at the end
+    #[serde(skip)]
+    position: f32,
+    /// How many mistakes it counts as, only set with `early_error_weight`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    weight: Option<f32>,
+    /// A British spelling the American dictionary flagged, see `british_spellings`
+    #[serde(skip)]
+    british: bool,
+    /// Where it starts, both from 1 and in chars, for --lint-only
+    #[serde(skip)]
+    line: usize,
+    #[serde(skip)]
+    column: usize,
+    /// harper's fixes, like `Replace with: “video”`
+    #[serde(skip)]
+    suggestions: Vec<String>,
+}
+
+const DIALECT: harper_core::Dialect = harper_core::Dialect::American;
+
+// The words in harper's word tokens, each split again on unicode word
+// boundaries: emoji aren't words and accented letters stay in their word.
+// harper can't tokenize CJK text, which has no spaces, so its unlintable
+// runs are split the same way and each character counts as a word
+fn unicode_words(doc: &Document, tokens: &[Token]) -> Vec<String> {
+    tokens
+        .iter()
+        .filter(|t| t.kind.is_word() || t.kind.is_unlintable())
+        .flat_map(|t| {
+            doc.get_span_content_str(&t.span)
+                .unicode_words()
+                .map(|w| w.to_string())
+                .collect::<Vec<_>>()
+        })
+        .collect()
+}
+
+// Words in a stretch of text before a config's counting policy is applied,
+// see Config::count_words
+#[derive(Debug, Clone, Copy, Default)]
+pub(crate) struct WordCount {
+    /// Each part of a hyphenated word counted, each contraction once
+    pub parts: usize,
+    /// Hyphens between two letters or digits, "mother-in-law" has two
+    pub hyphen_joins: usize,
+    /// Words with an apostrophe inside, like "don't"
+    pub contractions: usize,
+}
+impl WordCount {
+    // `words` as unicode_words split them out of `text`
+    fn of(words: &[String], text: &str) -> WordCount {
+        let chars: Vec<char> = text.chars().collect();
+        WordCount {
+            parts: words.len(),
+            hyphen_joins: chars
+                .windows(3)
+                .filter(|w| w[1] == '-' && w[0].is_alphanumeric() && w[2].is_alphanumeric())
+                .count(),
+            contractions: words.iter().filter(|w| w.contains('\'')).count(),
+        }
+    }
+    // Plain text split on unicode word boundaries, without harper
+    fn of_text(text: &str) -> WordCount {
+        let words: Vec<String> = text.unicode_words().map(String::from).collect();
+        WordCount::of(&words, text)
+    }
+}
+
+// Every link blanked out with as many spaces as it has chars, so harper
+// doesn't flag domain names and slashes and spans still line up with the text
+fn mask_links(text: &str) -> String {
+    Rubric::link_regex()
+        .replace_all(text, |link: &regex::Captures| {
+            " ".repeat(link[0].chars().count())
+        })
+        .into_owned()
+}
+
+// The side of the Atlantic harper's dictionary puts a spelling on, None when
+// both spell it that way or it isn't a word
+fn spelling_dialect(dict: &FstDictionary, word: &str) -> Option<Dialect> {
+    let metadata = dict
+        .get_word_metadata_str(word)
+        .or_else(|| dict.get_word_metadata_str(&word.to_lowercase()))?;
+    let dialects = metadata.dialects;
+    match (
+        dialects.is_dialect_enabled_strict(Dialect::American),
+        dialects.is_dialect_enabled_strict(Dialect::British),
+    ) {
+        (true, false) => Some(Dialect::American),
+        (false, true) => Some(Dialect::British),
+        _ => None,
+    }
+}
+
+// Every lint from the `rules` named with the total word count, which
+// category takes each lint is up to the config. With `strip_urls` links are
+// masked first and aren't words
+fn bucket_lints(text: &str, strip_urls: bool, rules: &[&str]) -> LintRun {
+    let linted = if strip_urls {
+        profile::time("lint;mask_links", || mask_links(text))
+    } else {
+        text.to_string()
+    };
+    let doc: Document = profile::time("lint;document", || {
+        Document::new_plain_english_curated(&linted)
+    });
+    let mut linter: LintGroup = LintGroup::default();
+    let dict = profile::time("lint;dictionary", FstDictionary::curated);
+    let spellcheck: SpellCheck<Arc<FstDictionary>> = SpellCheck::new(dict.clone(), DIALECT);
+    linter.add("Spelling", spellcheck);
+    linter.add("AnA", AnA);
+    linter.add("CapitalizePersonalPronouns", CapitalizePersonalPronouns);
+    linter.add("CommaFixes", CommaFixes);
+    linter.add("CompoundNouns", CompoundNouns::default());
+    linter.add("CorrectNumberSuffix", CorrectNumberSuffix);
+    linter.add("CurrencyPlacement", CurrencyPlacement::default());
+    linter.add("DiscourseMarkers", DiscourseMarkers::default());
+    linter.add("EllipsisLength", EllipsisLength);
+    linter.add("HopHope", HopHope::default());
+    linter.add("ItsContraction", ItsContraction::default());
+    linter.add("LetsConfusion", LetsConfusion::default());
+    linter.add("NounVerbConfusion", NounVerbConfusion::default());
+    linter.add("NumberSuffixCapitalization", NumberSuffixCapitalization);
+    linter.add(
+        "PhrasalVerbAsCompoundNoun",
+        PhrasalVerbAsCompoundNoun::default(),
+    );
+    linter.add("PronounContraction", PronounContraction::default());
+    linter.add("UnclosedQuotes", UnclosedQuotes);
+    linter.add(
+        "InflectedVerbAfterTo",
+        InflectedVerbAfterTo::new(dict.clone()),
+    );
+    linter.add(
+        "SentenceCapitalization",
+        SentenceCapitalization::new(dict.clone()),
+    );
+    // Reads the unmasked text, a line ending in a link doesn't need a period
+    struct Samslint {
+        text: String,
+    }
+    impl Linter for Samslint {
+        fn lint(&mut self, _: &Document) -> Vec<Lint> {
+            let mut lints = Vec::new();
+            let text = &self.text;
+            // spans count chars, not bytes, and include the newlines
+            let mut i = 0;
+            for raw in text.split_inclusive('\n') {
+                let line = raw.trim_end_matches(['\n', '\r']);
+                if !line.trim().ends_with(['!', '.', '?'])
+                    && !Rubric::contains_link(line)
+                    && !line.trim().is_empty()
+                {
+                    let lint = Lint {
+                        span: Span::new(i, i + line.chars().count()),
+                        lint_kind: LintKind::Punctuation,
+                        suggestions: vec![Suggestion::InsertAfter(vec!['.'])],
+                        message: "Missing period at end of sentence".to_string(),
+                        priority: 0,
+                    };
+                    lints.push(lint);
+                }
+                i += raw.chars().count();
+            }
+            lints
+        }
+
+        fn description(&self) -> &str {
+            "Check if line ends with punctuation"
+        }
+    }
+    linter.add(
+        "Sams Lint",
+        Samslint {
+            text: text.to_string(),
+        },
+    );
+    // one rule at a time, so a rule that panics on this text only loses its
+    // own lints instead of failing every lints category
+    let rules: Vec<String> = linter
+        .iter_keys()
+        .filter(|rule| rules.contains(rule))
+        .map(String::from)
+        .collect();
+    let mut lints = Vec::new();
+    let mut failed_rules = Vec::new();
+    let start = Instant::now();
+    for rule in rules {
+        linter.set_all_rules_to(Some(false));
+        linter.config.set_rule_enabled(&rule, true);
+        let step = format!("lint;rules;{}", rule);
+        match std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+            if cfg!(debug_assertions) && std::env::var(PANIC_RULE).is_ok_and(|r| r == rule) {
+                panic!("{} is set", PANIC_RULE);
+            }
+            profile::time(&step, || linter.lint(&doc))
+        })) {
+            Ok(found) => lints.extend(found),
+            Err(panic) => {
+                let reason = panic_reason(panic.as_ref());
+                log::error!("lint rule {} panicked: {}", rule, reason);
+                report::warning(
+                    "lint",
+                    &format!(
+                        "the {} rule failed on this text, its mistakes are left out: {}",
+                        rule, reason
+                    ),
+                );
+                failed_rules.push(rule);
+            }
+        }
+    }
+    profile::record("lint;rules", start.elapsed());
+    let (words, american) = profile::time("lint;words", || {
+        let words = unicode_words(&doc, doc.get_tokens());
+        let american = words
+            .iter()
+            .filter(|w| spelling_dialect(&dict, w) == Some(Dialect::American))
+            .cloned()
+            .collect();
+        (WordCount::of(&words, text), american)
+    });
+    log::debug!("{} lints in {} words", lints.len(), words.parts);
+    let bucketing = Instant::now();
+    let mut buckets: Vec<LintError> = Vec::new();
+    let v: Vec<char> = text.chars().collect();
+    // where each line starts in chars, lints are matched to theirs by span
+    let mut lines: Vec<(usize, bool)> = Vec::new();
+    let mut start = 0;
+    for line in text.split_inclusive('\n') {
+        lines.push((start, is_list_item(line)));
+        start += line.chars().count();
+    }
+    for error in lints {
+        let content = error.span.get_content_string({
+            let cs: &[char] = &v;
+            cs
+        });
+        log::debug!("{:?} lint: {}", error.lint_kind, error.message);
+        log::trace!(
+            "chars {}..{}: {:?}",
+            error.span.start,
+            error.span.end,
+            content
+        );
+        let (line, (line_start, list_item)) = lines
+            .iter()
+            .enumerate()
+            .rev()
+            .find(|(_, (start, _))| *start <= error.span.start)
+            .map(|(i, line)| (i + 1, *line))
+            .unwrap_or((1, (0, false)));
+        buckets.push(LintError {
+            category: categorize(error.lint_kind),
+            kind: error.lint_kind,
+            criterion: String::new(),
+            text: content.trim().to_string(),
+            message: error.message,
+            list_item,
+            position: error.span.start as f32 / v.len().max(1) as f32,
+            weight: None,
+            british: error.lint_kind == LintKind::Spelling
+                && spelling_dialect(&dict, content.trim()) == Some(Dialect::British),
+            line,
+            column: error.span.start - line_start + 1,
+            suggestions: error.suggestions.iter().map(|s| s.to_string()).collect(),
+        })
+    }
+    profile::record("lint;positions", bucketing.elapsed());
+    LintRun {
+        errors: buckets,
+        words,
+        american,
+        failed_rules,
+        ..Default::default()
+    }
+}
+
+// Lines starting with a bullet (- or *) or a number and a period, then a space
+fn is_list_item(line: &str) -> bool {
+    let line = line.trim_start();
+    if line.starts_with("- ") || line.starts_with("* ") {
+        return true;
+    }
+    let digits = line.len() - line.trim_start_matches(|c: char| c.is_ascii_digit()).len();
+    digits > 0 && line[digits..].starts_with(". ")
+}
+
+#[derive(Parser, Debug)]
+#[command(version, about)]
+struct Args {
+    #[command(subcommand)]
+    command: Option<Command>,
+    /// Files, directories of .txt files or http(s) urls to grade, defaults to input.txt
+    inputs: Vec<std::path::PathBuf>,
+    /// Skip files whose name matches this glob, e.g. '*.template.txt', can be repeated
+    #[arg(long, value_name = "GLOB")]
+    exclude: Vec<String>,
+    /// Only grade files modified since a date (2024-05-01, UTC) or a duration ago (1h, 2days)
+    #[arg(long, value_parser = input::parse_since, value_name = "WHEN")]
+    since: Option<std::time::SystemTime>,
+    /// Only grade this many of the matched files, picked at random
+    #[arg(long, value_name = "N")]
+    sample: Option<usize>,
+    /// Seed for --sample, the same seed picks the same files
+    #[arg(long, requires = "sample")]
+    seed: Option<u64>,
+    /// Estimate the "complete sentences" criterion from the text instead of prompting
+    #[arg(long)]
+    auto_questions: bool,
+    /// Skip the questions prompt when even full marks on it leave the final score below this percentage
+    #[arg(long, value_name = "PERCENT")]
+    skip_manual_if_below: Option<f32>,
+    /// How to print the result
+    #[arg(long, value_enum, default_value_t = Format::Text)]
+    format: Format,
+    /// Where the text came from, gdoc cleans up a Google Docs plain text export first
+    #[arg(long, value_enum, default_value_t = input::InputFormat::Plain)]
+    input_format: input::InputFormat,
+    /// The files' encoding (latin1, windows-1252, utf-16le...) when they aren't UTF-8, a byte order mark still wins
+    #[arg(long, value_parser = input::parse_encoding, value_name = "ENCODING")]
+    input_encoding: Option<&'static encoding_rs::Encoding>,
+    /// Render the text report with this tinytemplate instead of the default one
+    #[arg(long)]
+    template: Option<std::path::PathBuf>,
+    /// Whether British spellings like "colour" are mistakes, accepted, or accepted when not mixed with American ones
+    #[arg(long, value_enum)]
+    british_spellings: Option<BritishSpellings>,
+    /// Kinds of tokens whose spelling mistakes don't count (--spelling-ignore numbers,alphanumeric)
+    #[arg(long, value_enum, value_delimiter = ',')]
+    spelling_ignore: Vec<config::TokenClass>,
+    /// Blank out links before linting so domain names and slashes aren't flagged
+    #[arg(long)]
+    strip_urls_before_linting: bool,
+    /// Regex for the line ending a pasted assignment prompt, it and everything above aren't graded
+    #[arg(long, value_name = "REGEX")]
+    prompt_marker: Option<String>,
+    /// The assignment prompt, paragraphs at the top of a submission matching it aren't graded
+    #[arg(long, value_name = "FILE")]
+    prompt_file: Option<std::path::PathBuf>,
+    /// Expected answers, one per line: a phrase, 'number: 9.81 +- 0.05' or 'any: a | b'
+    #[arg(long, value_name = "FILE")]
+    answer_key: Option<std::path::PathBuf>,
+    /// The previous draft, only mistakes on lines changed since it count
+    #[arg(long, value_name = "PREVIOUS")]
+    changed_only: Option<std::path::PathBuf>,
+    /// Mistakes allowed per word in the spelling category, rounded up (0.01 = one per hundred words)
+    #[arg(long)]
+    spelling_rate: Option<f32>,
+    /// Capitalization mistakes allowed before the criterion fails, on top of its rate
+    #[arg(long, value_name = "N")]
+    caps_tolerance: Option<usize>,
+    /// Require at least this many paragraphs (separated by blank lines), 0 to skip
+    #[arg(long)]
+    min_paragraphs: Option<usize>,
+    /// Require this many distinct transition words (however, therefore, ...), 0 to skip
+    #[arg(long, value_name = "N")]
+    min_transitions: Option<usize>,
+    /// Flag sentences longer than this many words, 0 to skip
+    #[arg(long)]
+    max_sentence_words: Option<usize>,
+    /// How many over-long sentences are allowed before the criterion fails
+    #[arg(long)]
+    max_long_sentences: Option<usize>,
+    /// Regex for the line each answer starts on, e.g. '^Q[0-9]+:'
+    #[arg(long, value_name = "REGEX")]
+    split_on: Option<String>,
+    /// Require this many distinct sentences in every answer (see --split-on), 0 to skip
+    #[arg(long, value_name = "N")]
+    min_sentences_per_answer: Option<usize>,
+    /// A word or phrase that fails the submission when used, can be repeated
+    #[arg(long = "forbid", value_name = "PHRASE")]
+    forbidden_words: Vec<String>,
+    /// Add the settings used (and a hash of them) to the report
+    #[arg(long)]
+    include_config: bool,
+    /// Criteria the text report lists, in this order (--report-order questions,spelling)
+    #[arg(long, value_delimiter = ',', value_name = "CRITERIA")]
+    report_order: Vec<String>,
+    /// Only list the criteria that didn't get full marks in the text report
+    #[arg(long)]
+    failures_only: bool,
+    /// Show how each criterion adds up to the final score under the text report
+    #[arg(long)]
+    explain_score: bool,
+    /// Grade the text on the clipboard instead of input.txt (needs the clipboard feature)
+    #[arg(long)]
+    clipboard: bool,
+    /// Grade everything written to a named pipe, reopening it after each writer is done (Unix only)
+    #[arg(long, conflicts_with_all = ["diff_score", "baseline", "db", "clipboard"])]
+    watch: bool,
+    /// List every mistake, optionally only for some categories (--verbose=spelling,punctuation)
+    #[arg(long, value_enum, value_delimiter = ',', num_args = 0.., require_equals = true)]
+    verbose: Option<Vec<LintCategory>>,
+    /// Mistakes --verbose lists per category before summing up the rest, 0 lists them all
+    #[arg(long, default_value_t = 20, value_name = "N")]
+    max_errors_shown: usize,
+    /// Links to one of the link domains needed for full marks, fewer get partial credit
+    #[arg(long)]
+    min_links: Option<usize>,
+    /// Require the first line to be a title
+    #[arg(long)]
+    require_title: bool,
+    /// How picky the title check is
+    #[arg(long, value_enum)]
+    title_strictness: Option<TitleStrictness>,
+    /// Regex the title has to match instead of looking like one
+    #[arg(long)]
+    title_pattern: Option<String>,
+    /// Regex the last non-empty line has to match, like a "Submitted by: " sign-off
+    #[arg(long)]
+    closing_pattern: Option<String>,
+    /// Accept the closing on any line, not just the last
+    #[arg(long)]
+    closing_anywhere: bool,
+    /// Lowest Flesch-Kincaid grade level that passes the readability criterion
+    #[arg(long)]
+    min_grade_level: Option<f32>,
+    /// Highest Flesch-Kincaid grade level that passes the readability criterion
+    #[arg(long)]
+    max_grade_level: Option<f32>,
+    /// Experimental: share of verbs (0-0.5) allowed in the less used of past and present tense
+    #[arg(long)]
+    max_tense_mix: Option<f32>,
+    /// Experimental: share of sentences (0-1) allowed to start with the same word
+    #[arg(long)]
+    max_same_opener: Option<f32>,
+    /// Experimental: share of the words (0-1) allowed in the longest paragraph
+    #[arg(long)]
+    max_paragraph_share: Option<f32>,
+    /// Experimental heuristic: filler phrases ('it is important to note'...) allowed per 100 words
+    #[arg(long)]
+    max_filler_density: Option<f32>,
+    /// Experimental: how many times a mistake at the very start counts, falling to once at the end
+    #[arg(long, value_name = "WEIGHT")]
+    early_error_weight: Option<f32>,
+    /// How --early-error-weight falls off through the text
+    #[arg(long, value_enum)]
+    early_error_decay: Option<config::Decay>,
+    /// Point of view the text has to be written in
+    #[arg(long, value_enum)]
+    voice: Option<Voice>,
+    /// First-person pronouns allowed with --voice third
+    #[arg(long, value_name = "N")]
+    max_first_person: Option<usize>,
+    /// Require a complete sentence (subject and finite verb) in every paragraph, best effort
+    #[arg(long)]
+    require_complete_paragraphs: bool,
+    /// Require every quotation mark to be closed, apostrophes aside
+    #[arg(long)]
+    require_balanced_quotes: bool,
+    /// CSV (file,answer) or JSON object of each file's questions answer, instead of prompting
+    #[arg(long)]
+    seed_answers: Option<std::path::PathBuf>,
+    /// Answer for files without one in --seed-answers, prompts when unset
+    #[arg(long, value_enum)]
+    default_answer: Option<Answer>,
+    /// CSV (file,comment) or JSON object of the grader's comment on each file
+    #[arg(long)]
+    comments: Option<std::path::PathBuf>,
+    /// Ask for a comment after scoring each file that --comments has none for
+    #[arg(long)]
+    ask_comment: bool,
+    /// CSV of file,expected score to compare the final scores against
+    #[arg(long)]
+    baseline: Option<std::path::PathBuf>,
+    /// Percentage points a score may differ from the baseline
+    #[arg(long, default_value_t = 0.0)]
+    tolerance: f32,
+    /// Whether criteria that couldn't be evaluated count as zero or are left out of the score
+    #[arg(long, value_enum)]
+    unevaluated: Option<Unevaluated>,
+    /// How displayed scores are rounded, nearest (halves up) by default
+    #[arg(long, value_enum)]
+    rounding: Option<config::Rounding>,
+    /// Mistakes not counted on list items (lines starting with -, * or 1.)
+    #[arg(long, value_enum)]
+    list_exemption: Option<config::ListExemption>,
+    /// Whether a hyphenated word counts as one word or one per part, in every word count
+    #[arg(long, value_enum)]
+    hyphenated_words: Option<config::HyphenatedWords>,
+    /// Whether a contraction counts as one word or two, in every word count
+    #[arg(long, value_enum)]
+    contractions: Option<config::Contractions>,
+    /// Grade with this config too and print how each final score moves, without the reports
+    #[arg(long, value_name = "CONFIG")]
+    diff_score: Option<std::path::PathBuf>,
+    /// List the mistakes first and dismiss false positives before scoring
+    #[arg(long)]
+    review: bool,
+    /// Files graded at once in a batch, the number of cores by default. Runs that prompt grade one at a time
+    #[arg(long, value_name = "N")]
+    jobs: Option<std::num::NonZeroUsize>,
+    /// Grade every matched file together as one submission, in order, for group work
+    #[arg(long, conflicts_with_all = ["watch", "clipboard", "seed_answers", "comments"])]
+    aggregate: bool,
+    /// The harper rules to start from
+    #[arg(long, value_enum, value_name = "PRESET")]
+    linters: Option<LinterPreset>,
+    /// Run only these harper rules, e.g. Spelling,CommaFixes
+    #[arg(long, value_delimiter = ',', value_name = "RULES")]
+    only_linters: Vec<String>,
+    /// Don't run this harper rule, repeatable
+    #[arg(long, value_name = "RULE")]
+    disable: Vec<String>,
+    /// Also write each file's full JSON result next to it, e.g. essay.quickgrade.json
+    #[arg(long, conflicts_with_all = ["diff_score", "lint_only"])]
+    emit_sidecar: bool,
+    /// Store every file's scores in this SQLite database (needs the sqlite feature)
+    #[arg(long)]
+    db: Option<std::path::PathBuf>,
+    /// Assignment name for the report header, --db stores rows under it
+    #[arg(long)]
+    assignment: Option<String>,
+    /// Due date for the report header
+    #[arg(long, value_name = "DATE")]
+    due_date: Option<String>,
+    /// Who graded it, for the report header
+    #[arg(long)]
+    grader: Option<String>,
+    /// Print errors and warnings on stderr as JSON objects, one per line
+    #[arg(long)]
+    json_errors: bool,
+    /// Color the text report: auto colors it on a terminal unless NO_COLOR is set
+    #[arg(long, value_enum, default_value_t = ColorChoice::Auto, value_name = "WHEN")]
+    color: ColorChoice,
+    /// Same as --color never
+    #[arg(long)]
+    no_color: bool,
+    /// Rubric settings, defaults to ./quickgrade.toml when it exists, repeat it to grade under each
+    #[arg(long)]
+    config: Vec<std::path::PathBuf>,
+    /// The rubric settings as a JSON object, in place of a config file
+    #[arg(long, value_name = "JSON", conflicts_with = "config")]
+    rubric_json: Option<String>,
+    /// Print the rubric that would be used and exit
+    #[arg(long)]
+    show_rubric: bool,
+    /// Only list what harper finds, with positions and suggestions, without grading or prompting
+    #[arg(long, conflicts_with_all = ["diff_score", "baseline", "db", "review", "watch"])]
+    lint_only: bool,
+    /// Time each step of grading, linting rule by rule, and print it on stderr at the end
+    #[arg(long, value_enum, value_name = "FORMAT", num_args = 0..=1, default_missing_value = "tree")]
+    profile: Option<profile::ProfileFormat>,
+    /// Check the configs, the inputs and the dictionary, then exit without grading
+    #[arg(long, conflicts_with = "watch")]
+    dry_run: bool,
+    /// Print the compiled in features and supported formats as JSON and exit
+    #[arg(long)]
+    capabilities: bool,
+}
+
+#[derive(clap::Subcommand, Debug)]
+enum Command {
+    /// Write a commented quickgrade.toml with every setting at its default
+    Init {
+        /// Also write a sample input.txt to try it on
+        #[arg(long)]
+        sample: bool,
+        /// Overwrite the files when they are already there
+        #[arg(long)]
+        force: bool,
+    },
+    /// Grade the text POSTed to /grade over HTTP (needs the server feature)
+    Serve {
+        #[arg(long, default_value_t = 8080)]
+        port: u16,
+        /// Address to listen on, 0.0.0.0 to take requests from other machines
+        #[arg(long, default_value = "127.0.0.1")]
+        host: String,
+    },
+}
+
+#[derive(clap::ValueEnum, Clone, Copy, Debug, PartialEq)]
+enum Format {
+    Text,
+    Json,
+    /// One compact JSON object per submission per line, printed as each is graded
+    Jsonl,
+    /// A header, then one row per submission with the final score and each criterion in percent
+    Csv,
+    /// Writes a feedback sheet next to the input, needs the pdf feature
+    Pdf,
+}
+
+#[derive(clap::ValueEnum, Clone, Copy, Debug, PartialEq)]
+enum ColorChoice {
+    Auto,
+    /// Even when piped, for tools that render ANSI colors
+    Always,
+    Never,
+}
+impl ColorChoice {
+    // An explicit --color always beats NO_COLOR, which only turns off the default
+    fn enabled(self) -> bool {
+        match self {
+            ColorChoice::Always => true,
+            ColorChoice::Never => false,
+            ColorChoice::Auto => {
+                std::io::IsTerminal::is_terminal(&std::io::stdout())
+                    && std::env::var_os("NO_COLOR").is_none_or(|v| v.is_empty())
+            }
+        }
+    }
+}
+
+// One graded line of the report, a config category or an optional check
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct Criterion {
+    /// Stable identifier, e.g. `paragraphs`
+    name: String,
+    description: String,
+    #[serde(flatten)]
+    grade: Grade,
+    /// Relative to the other criteria, see `Rubric::weight` for the share
+    weight: f32,
+    /// What was measured, e.g. `1 found, 3 required`
+    detail: String,
+    /// Reported but left out of the score, a category with `severity = "warn"`
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    warning: bool,
+}
+
+// Flesch-Kincaid stats, only as good as the syllable guess
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+struct Readability {
+    words: usize,
+    sentences: usize,
+    syllables: usize,
+    /// 0-100, higher is easier
+    reading_ease: f32,
+    /// US school grade
+    grade_level: f32,
+}
+
+// Vowel groups, minus a silent e at the end ("make" but not "table"), at least one
+fn syllables(word: &str) -> usize {
+    let word = word.to_lowercase();
+    let is_vowel = |c: char| "aeiouy".contains(c);
+    let mut count = 0;
+    let mut previous = false;
+    for c in word.chars() {
+        let vowel = is_vowel(c);
+        if vowel && !previous {
+            count += 1;
+        }
+        previous = vowel;
+    }
+    if word.ends_with('e') && !word.ends_with("le") && count > 1 {
+        count -= 1;
+    }
+    count.max(1)
+}
+
+impl Readability {
+    // Words are counted under the config's policy, syllables per part
+    fn measure(contents: &str, config: &Config) -> Readability {
+        let doc = Document::new_plain_english_curated(contents);
+        let parts = unicode_words(&doc, doc.get_tokens());
+        let words = config.count_words(WordCount::of(&parts, contents));
+        let sentences = doc
+            .iter_sentences()
+            .filter(|s| s.iter_words().next().is_some())
+            .count()
+            .max(1);
+        let syllables: usize = parts.iter().map(|w| syllables(w)).sum();
+        let per_sentence = words.max(1) as f32 / sentences as f32;
+        let per_word = syllables as f32 / words.max(1) as f32;
+        Readability {
+            words,
+            sentences,
+            syllables,
+            reading_ease: 206.835 - 1.015 * per_sentence - 84.6 * per_word,
+            grade_level: 0.39 * per_sentence + 11.8 * per_word - 15.59,
+        }
+    }
+}
+
+// What was asked for a manually graded criterion and what it was decided on
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct ManualAnswer {
+    criterion: String,
+    prompt: String,
+    /// Full marks, a plain yes
+    answer: bool,
+    /// Percent of the criterion given, 0 or 100 unless the grader typed a score
+    score: f32,
+    /// Guessed from the text by --auto-questions rather than given by the grader
+    estimated: bool,
+}
+
+// The opener check passes anything shorter
+const MIN_OPENER_SENTENCES: usize = 3;
+
+// What went into grading, for dashboards rather than students
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+struct Diagnostics {
+    /// Milliseconds harper took to lint
+    lint_ms: f64,
+    /// Every lint harper raised, counted against a criterion or not
+    lints: usize,
+    /// Mistakes counted against each lints criterion, by name
+    per_category: BTreeMap<String, usize>,
+}
+
+const QUESTIONS_PROMPT: &str = "Complete sentences and all questions answered?";
+// Invalid answers a prompt takes before giving up, so piped input or a flaky
+// terminal can't keep it asking forever
+const PROMPT_ATTEMPTS: usize = 5;
+
+/// A graded submission: every criterion, the mistakes behind them and the score
+///
+/// Serialized (`--format json`, sidecars, `quickgrade serve`) its field names
+/// are stable and deserialize back into a `Rubric`: `criteria`, each with
+/// `name`, `description`, `grade`, `status`, `weight`, `detail` and
+/// `warning` when set, then `errors`, `unevaluated`, `rounding` and
+/// `diagnostics`. The other fields only appear when they apply, see the
+/// README's JSON output section
+// The serialized names are part of the JSON output, don't rename them
+#[derive(Default, Debug, Clone, Serialize, Deserialize)]
+pub struct Rubric {
+    /// The config categories followed by the optional criteria that were turned on
+    criteria: Vec<Criterion>,
+    /// Every mistake that counted against a category
+    #[serde(default)]
+    errors: Vec<LintError>,
+    /// Only measured when the readability criterion is on
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    readability: Option<Readability>,
+    /// What criteria that couldn't be evaluated count for
+    #[serde(default)]
+    unevaluated: Unevaluated,
+    /// How the scores are rounded for display
+    #[serde(default)]
+    rounding: Rounding,
+    /// Set when harper panicked, every lints category is failed then
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    lint_failure: Option<String>,
+    /// The answers behind the manual criteria, for when a grade gets disputed
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    manual: Vec<ManualAnswer>,
+    /// Mistakes the grader dismissed under --review
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    dismissed: Vec<LintError>,
+    /// Lint rules that panicked on the text, their mistakes are missing
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    failed_rules: Vec<String>,
+    #[serde(default)]
+    diagnostics: Diagnostics,
+    /// The grader's own feedback, from --comments or --ask-comment
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    comment: Option<String>,
+    /// British spellings british_spellings kept from counting
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    british_accepted: Vec<String>,
+    /// Misspelled tokens spelling_ignore kept from counting
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    ignored_tokens: Vec<String>,
+    /// The assignment prompt left out from the top, see `prompt_marker`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    prompt_stripped: Option<prompt::PromptStripped>,
+    /// The files graded together under --aggregate
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    aggregated: Option<Aggregated>,
+    /// The section rubrics behind a sectioned assignment's total, see `Config::sections`
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    sections: Vec<SectionGrade>,
+}
+
+// --aggregate: the files behind one rubric and their words together
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct Aggregated {
+    files: Vec<String>,
+    words: usize,
+}
+impl Aggregated {
+    fn describe(&self) -> String {
+        format!(
+            "Graded together: {} ({} words)",
+            self.files.join(", "),
+            self.words
+        )
+    }
+}
+
+// How a rubric gets rendered, none of it changes the score
+#[derive(Default)]
+struct ReportOptions<'a> {
+    /// Settings to stamp on the report (--include-config)
+    config: Option<&'a Config>,
+    /// Categories whose mistakes are listed one by one (--verbose)
+    verbose: Vec<LintCategory>,
+    /// How many of them per category, 0 for no limit
+    max_errors_shown: usize,
+    metadata: Metadata,
+    /// ANSI colors in the text report
+    color: bool,
+    /// The arithmetic behind the final score under the text report (--explain-score)
+    explain_score: bool,
+    /// Leave the criteria with full marks out of the text report (--failures-only)
+    failures_only: bool,
+    /// Criteria the text report lists and in what order, all of them when empty
+    order: Vec<String>,
+}
+
+// What a report is for and who graded it, on every report so archived ones
+// explain themselves
+#[derive(Debug, Clone, Default, Serialize)]
+struct Metadata {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    assignment: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    due_date: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    grader: Option<String>,
+}
+impl Metadata {
+    fn new(config: &Config) -> Metadata {
+        Metadata {
+            assignment: config.assignment.clone(),
+            due_date: config.due_date.clone(),
+            grader: config.grader.clone(),
+        }
+    }
+    fn is_empty(&self) -> bool {
+        self.assignment.is_none() && self.due_date.is_none() && self.grader.is_none()
+    }
+}
+
+// Bump whenever a field of the JSON report is renamed, removed or changes
+// meaning, and note it in the README
+const SCHEMA_VERSION: u32 = 3;
+
+#[derive(Serialize)]
+struct JsonReport<'a> {
+    schema_version: u32,
+    /// Only in batch and jsonl output
+    #[serde(skip_serializing_if = "Option::is_none")]
+    file: Option<&'a str>,
+    /// Which --config it was graded under, only when there are several
+    #[serde(skip_serializing_if = "Option::is_none")]
+    config_file: Option<&'a str>,
+    #[serde(flatten)]
+    metadata: &'a Metadata,
+    #[serde(flatten)]
+    rubric: &'a Rubric,
+    score: f32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    config: Option<&'a Config>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    config_hash: Option<String>,
+}
+// Quotes a CSV field when it needs it
+fn csv_field(text: &str) -> String {
+    if text.contains([',', '"', '\n']) {
+        format!("\"{}\"", text.replace('"', "\"\""))
+    } else {
+        text.to_string()
+    }
+}
+
+// A link as (host, rest) for telling duplicates apart: the scheme, a www.,
+// the fragment, a trailing slash and the query are dropped and the host is
+// lowercased. The v parameter stays since it is the video on youtube.com/watch
+fn normalize_link(link: &str) -> (String, String) {
+    let link = link.split_once("://").map_or(link, |(_, rest)| rest);
+    let link = link.split('#').next().unwrap_or_default();
+    let (link, query) = link.split_once('?').unwrap_or((link, ""));
+    let (host, path) = link.split_once('/').unwrap_or((link, ""));
+    let host = host.to_lowercase();
+    let host = host.strip_prefix("www.").unwrap_or(&host).to_string();
+    let mut rest = path.trim_end_matches('/').to_string();
+    if let Some(video) = query.split('&').find(|p| p.starts_with("v=")) {
+        rest += &format!("?{}", video);
+    }
+    (host, rest)
+}
+
+// Every percentage we print that isn't a score goes through this so the
+// report rounds one way, the scores go through the config's Rounding
+fn round_percent(percent: f32) -> f32 {
+    percent.round()
+}
+
+impl Rubric {
+    // Whether a criterion is part of the final score at all
+    fn scored(&self, criterion: &Criterion) -> bool {
+        !criterion.warning
+            && (criterion.grade.val.is_some() || self.unevaluated == Unevaluated::Zero)
+    }
+    // Short of full marks on a criterion that was evaluated or counts anyway,
+    // what --failures-only keeps
+    fn needs_work(&self, criterion: &Criterion) -> bool {
+        !criterion.grade.get()
+            && (self.scored(criterion) || (criterion.warning && criterion.grade.val.is_some()))
+    }
+    fn total_weight(&self) -> f32 {
+        self.criteria
+            .iter()
+            .filter(|c| self.scored(c))
+            .map(|c| c.weight)
+            .sum()
+    }
+    fn score_fraction(&self) -> f32 {
+        let total = self.total_weight();
+        if total == 0.0 {
+            return 0.0; // nothing could be evaluated
+        }
+        self.criteria
+            .iter()
+            .filter(|c| self.scored(c))
+            .map(|c| c.grade.perc() * c.weight)
+            .sum::<f32>()
+            / total
+    }
+    /// The final score in percent, rounded
+    pub fn score_percent(&self) -> f32 {
+        self.rounding.apply(self.score_fraction() * 100.0)
+    }
+    // Percentage of the final score a criterion is worth
+    fn weight(&self, criterion: &Criterion) -> f32 {
+        round_percent(self.weight_exact(criterion))
+    }
+    fn weight_exact(&self, criterion: &Criterion) -> f32 {
+        if !self.scored(criterion) {
+            return 0.0;
+        }
+        100.0 * criterion.weight / self.total_weight()
+    }
+    // Percentage of the final score a criterion earned
+    fn points(&self, criterion: &Criterion) -> f32 {
+        self.rounding
+            .apply(criterion.grade.perc() * self.weight_exact(criterion))
+    }
+    // --explain-score: each criterion's grade times its share of the final
+    // score, and what that adds up to, for when a score gets disputed
+    fn explain_score(&self) -> String {
+        let total = self.total_weight();
+        let mut lines = vec!["How the score adds up:".to_string()];
+        let mut sum = 0.0;
+        for c in &self.criteria {
+            let line = if c.warning {
+                "warning only, not part of the score".to_string()
+            } else if !self.scored(c) {
+                "not evaluated, left out so the others share its weight".to_string()
+            } else {
+                let share = self.weight_exact(c);
+                let contribution = c.grade.perc() * share;
+                sum += contribution;
+                format!(
+                    "{:.2}{} x {:.1}% (weight {} of {}) = {:.1}",
+                    c.grade.perc(),
+                    if c.grade.val.is_none() {
+                        " (not evaluated)"
+                    } else {
+                        ""
+                    },
+                    share,
+                    c.weight,
+                    total,
+                    contribution
+                )
+            };
+            lines.push(format!("\t{}: {}", c.name, line));
+        }
+        if total == 0.0 {
+            lines.push("\tNothing could be evaluated, the score is 0%".to_string());
+        } else {
+            lines.push(format!(
+                "\tSum: {:.1}, rounded to {}%",
+                sum,
+                self.score_percent()
+            ));
+        }
+        lines.join("\n")
+    }
+    fn errors_in<'a>(&'a self, criterion: &'a Criterion) -> impl Iterator<Item = &'a LintError> {
+        self.errors.iter().filter(|e| e.criterion == criterion.name)
+    }
+    // Spelling mistakes starting with a capital, usually names the curated
+    // dictionary doesn't know rather than real mistakes
+    fn capitalized_misspellings(&self, criterion: &Criterion) -> usize {
+        self.errors_in(criterion)
+            .filter(|e| e.category == Some(LintCategory::Spelling))
+            .filter(|e| e.text.starts_with(|c: char| c.is_uppercase()))
+            .count()
+    }
+    // Links pointing at one of the domains (or a subdomain) as (unique,
+    // pasted), the same video pasted twice only counts once
+    fn good_links(contents: &str, domains: &[String]) -> (usize, usize) {
+        let mut links: Vec<(String, String)> = Rubric::links_to(contents, domains)
+            .into_iter()
+            .map(normalize_link)
+            .collect();
+        let pasted = links.len();
+        links.sort();
+        links.dedup();
+        (links.len(), pasted)
+    }
+    // Every link, as written, whose host is one of the domains or a subdomain
+    fn links_to<'a>(contents: &'a str, domains: &[String]) -> Vec<&'a str> {
+        Rubric::link_regex()
+            .find_iter(contents)
+            .map(|m| m.as_str())
+            .filter(|link| {
+                let (host, _) = normalize_link(link);
+                domains
+                    .iter()
+                    .any(|d| host == *d || host.ends_with(&format!(".{}", d)))
+            })
+            .collect()
+    }
+    fn link_regex() -> Regex {
+        Regex::new(r"https?:\/\/(www\.)?[-a-zA-Z0-9@:%._\+~#=]{1,256}\.[a-zA-Z0-9()]{1,6}\b([-a-zA-Z0-9()@:%_\+.~#?&//=]*)").unwrap()
+    }
+    fn contains_link(contents: &str) -> bool {
+        Rubric::link_regex().is_match(contents)
+    }
+    // Rough stand-in for the manual check: every sentence with words in it
+    // needs a verb that isn't just an -ing form and has to end in . ! or ?
+    fn complete_sentences(contents: &str) -> bool {
+        let doc = Document::new_plain_english_curated(contents);
+        if contents.trim().is_empty() {
+            return false;
+        }
+        for sentence in doc.iter_sentences() {
+            if sentence.iter_words().next().is_none() {
+                continue; // links and stray punctuation
+            }
+            let has_verb = sentence
+                .iter_words()
+                .any(|t| t.kind.is_verb() && !t.kind.is_verb_progressive_form());
+            let text = match sentence.span() {
+                Some(span) => doc.get_span_content_str(&span),
+                None => continue,
+            };
+            if !has_verb || !text.trim().ends_with(['!', '.', '?']) {
+                return false;
+            }
+        }
+        true
+    }
+    // A paragraph is a run of non-empty lines, blank lines separate them
+    fn count_paragraphs(contents: &str) -> usize {
+        let mut count = 0;
+        let mut in_paragraph = false;
+        for line in contents.lines() {
+            if line.trim().is_empty() {
+                in_paragraph = false;
+            } else if !in_paragraph {
+                in_paragraph = true;
+                count += 1;
+            }
+        }
+        count
+    }
+    // The text of each paragraph, split the way count_paragraphs does
+    fn paragraphs(contents: &str) -> Vec<String> {
+        let mut paragraphs: Vec<String> = Vec::new();
+        let mut in_paragraph = false;
+        for line in contents.lines() {
+            if line.trim().is_empty() {
+                in_paragraph = false;
+                continue;
+            }
+            match paragraphs.last_mut() {
+                Some(paragraph) if in_paragraph => {
+                    paragraph.push('\n');
+                    paragraph.push_str(line);
+                }
+                _ => paragraphs.push(line.to_string()),
+            }
+            in_paragraph = true;
+        }
+        paragraphs
+    }
+    fn paragraph_words(contents: &str, config: &Config) -> Vec<usize> {
+        Rubric::paragraphs(contents)
+            .iter()
+            .map(|p| config.count_words(WordCount::of_text(p)))
+            .collect()
+    }
+    // Best effort: a noun or pronoun followed later on by a verb that isn't
+    // an -ing form. The tagger's part of speech wins over the dictionary's,
+    // which lists every part of speech a word can be
+    fn has_subject_and_verb(sentence: &[Token]) -> bool {
+        let mut subject = false;
+        for word in sentence.iter_words() {
+            let tag = match &word.kind {
+                TokenKind::Word(Some(metadata)) => metadata.pos_tag,
+                _ => None,
+            };
+            let verb = match tag {
+                Some(UPOS::AUX) => true,
+                Some(UPOS::VERB) => !word.kind.is_verb_progressive_form(),
+                Some(_) => false,
+                None => word.kind.is_verb() && !word.kind.is_verb_progressive_form(),
+            };
+            if subject && verb {
+                return true;
+            }
+            subject |= match tag {
+                Some(tag) => matches!(tag, UPOS::NOUN | UPOS::PROPN | UPOS::PRON),
+                None => word.kind.is_nominal(),
+            };
+        }
+        false
+    }
+    // 1-based numbers of the paragraphs without a complete sentence, one
+    // with a subject and a verb that ends in . ! or ?
+    fn incomplete_paragraphs(contents: &str) -> Vec<usize> {
+        Rubric::paragraphs(contents)
+            .iter()
+            .enumerate()
+            .filter(|(_, paragraph)| {
+                let doc = Document::new_plain_english_curated(paragraph);
+                !doc.iter_sentences().any(|sentence| {
+                    let ended = sentence.span().is_some_and(|span| {
+                        doc.get_span_content_str(&span)
+                            .trim()
+                            .ends_with(['!', '.', '?'])
+                    });
+                    ended && Rubric::has_subject_and_verb(sentence)
+                })
+            })
+            .map(|(i, _)| i + 1)
+            .collect()
+    }
+    // line:column (from 1, in chars) of every quotation mark left open at
+    // the end of its paragraph or closing nothing. A ' between letters, before
+    // a digit, starting an elision like 'em or after a letter with no quote
+    // open is an apostrophe. ‘ is left alone since clean drops its ’
+    fn unclosed_quotes(contents: &str) -> Vec<(usize, usize)> {
+        const ELISIONS: &[&str] = &["em", "n", "til", "cause", "tis", "twas", "bout", "round"];
+        let mut unclosed = Vec::new();
+        let (mut double, mut single) = (None, None);
+        for (i, line) in contents.lines().enumerate() {
+            if line.trim().is_empty() {
+                unclosed.extend(double.take());
+                unclosed.extend(single.take());
+                continue;
+            }
+            let chars: Vec<char> = line.chars().collect();
+            for (j, c) in chars.iter().enumerate() {
+                let at = (i + 1, j + 1);
+                let before = j.checked_sub(1).map(|j| chars[j]);
+                let after = chars.get(j + 1).copied();
+                let word_before = before.is_some_and(char::is_alphanumeric);
+                let word_after = after.is_some_and(char::is_alphanumeric);
+                match c {
+                    '"' | '”' if double.is_some() => double = None,
+                    '”' => unclosed.push(at),
+                    '"' | '“' => unclosed.extend(double.replace(at)),
+                    '\'' if word_before && word_after => {}
+                    '\'' if word_after => {
+                        let word: String = chars[j + 1..]
+                            .iter()
+                            .take_while(|c| c.is_alphanumeric())
+                            .collect();
+                        let elision = ELISIONS.contains(&word.to_lowercase().as_str())
+                            || word.starts_with(|c: char| c.is_ascii_digit());
+                        if !elision && single.is_none() {
+                            single = Some(at);
+                        }
+                    }
+                    '\'' if word_before || before.is_some_and(|c| c.is_ascii_punctuation()) => {
+                        single = None;
+                    }
+                    _ => {}
+                }
+            }
+        }
+        unclosed.extend(double);
+        unclosed.extend(single);
+        unclosed.sort();
+        unclosed
+    }
+    // Each word once ignoring case, in the order they first appear
+    fn distinct<'a>(words: impl Iterator<Item = &'a String>) -> Vec<String> {
+        let mut seen: Vec<String> = Vec::new();
+        for word in words {
+            if !seen.iter().any(|w| w.eq_ignore_ascii_case(word)) {
+                seen.push(word.clone());
+            }
+        }
+        seen
+    }
+    fn long_sentences(contents: &str, config: &Config) -> usize {
+        let doc = Document::new_plain_english_curated(contents);
+        doc.iter_sentences()
+            .filter(|sentence| {
+                let text = sentence
+                    .span()
+                    .map(|span| doc.get_span_content_str(&span))
+                    .unwrap_or_default();
+                let count = WordCount::of(&unicode_words(&doc, sentence), &text);
+                config.count_words(count) > config.max_sentence_words
+            })
+            .count()
+    }
+    // First-person pronouns as written, by harper's pronoun data plus the
+    // possessives it doesn't mark as first person
+    fn first_person(contents: &str) -> Vec<String> {
+        const POSSESSIVES: [&str; 4] = ["my", "mine", "our", "ours"];
+        let doc = Document::new_plain_english_curated(contents);
+        doc.iter_words()
+            .filter(|word| {
+                word.kind.is_first_person_singular_pronoun()
+                    || word.kind.is_first_person_plural_pronoun()
+                    || POSSESSIVES
+                        .contains(&doc.get_span_content_str(&word.span).to_lowercase().as_str())
+            })
+            .map(|word| doc.get_span_content_str(&word.span))
+            .collect()
+    }
+    // Experimental: (past, present) verb counts for words harper's tagger
+    // marks as verbs. The dictionary rarely knows the form, so regular -ed
+    // and -s endings and the common irregular verbs fill in. Bare lemmas are
+    // skipped since "walk" could be present or an infinitive
+    fn verb_tenses(contents: &str) -> (usize, usize) {
+        const PAST: [&str; 16] = [
+            "was", "were", "had", "did", "went", "saw", "got", "came", "made", "said", "took",
+            "ran", "knew", "thought", "felt", "told",
+        ];
+        const PRESENT: [&str; 5] = ["am", "is", "are", "has", "does"];
+        let doc = Document::new_plain_english_curated(contents);
+        let mut past = 0;
+        let mut present = 0;
+        for word in doc.iter_words() {
+            let TokenKind::Word(Some(metadata)) = &word.kind else {
+                continue;
+            };
+            if !matches!(metadata.pos_tag, Some(UPOS::VERB | UPOS::AUX)) {
+                continue;
+            }
+            let text = doc.get_span_content_str(&word.span).to_lowercase();
+            if word.kind.is_verb_past_form()
+                || text.ends_with("ed")
+                || PAST.contains(&text.as_str())
+            {
+                past += 1;
+            } else if word.kind.is_verb_third_person_singular_present_form()
+                || PRESENT.contains(&text.as_str())
+                || (text.ends_with('s') && !text.ends_with("ss"))
+            {
+                present += 1;
+            }
+        }
+        (past, present)
+    }
+    // --split-on: each answer as (label, text), the label being what matched
+    // or `answer N` when that's blank. The whole text when nothing matches
+    fn answers(contents: &str, split_on: Option<&str>) -> Vec<(String, String)> {
+        let mut answers: Vec<(String, String)> = Vec::new();
+        // checked when the config was loaded
+        if let Some(regex) = split_on.map(|pattern| Regex::new(pattern).unwrap()) {
+            for line in contents.lines() {
+                if let Some(found) = regex.find(line.trim()) {
+                    let label = match found.as_str().trim() {
+                        "" => format!("answer {}", answers.len() + 1),
+                        label => label.to_string(),
+                    };
+                    answers.push((label, line.trim()[found.end()..].to_string()));
+                } else if let Some((_, text)) = answers.last_mut() {
+                    text.push('\n');
+                    text.push_str(line);
+                }
+            }
+        }
+        if answers.is_empty() {
+            answers.push(("the answer".to_string(), contents.to_string()));
+        }
+        answers
+    }
+    // Sentences with words in them, one repeated word for word counts once
+    fn distinct_sentences(text: &str) -> usize {
+        let doc = Document::new_plain_english_curated(text);
+        let mut seen: Vec<String> = Vec::new();
+        for sentence in doc.iter_sentences() {
+            let Some(span) = sentence.span() else {
+                continue;
+            };
+            if sentence.iter_words().next().is_none() {
+                continue; // links and stray punctuation
+            }
+            let text = doc.get_span_content_str(&span).trim().to_lowercase();
+            if !seen.contains(&text) {
+                seen.push(text);
+            }
+        }
+        seen.len()
+    }
+    // Experimental: the word most sentences start with, ignoring case, as
+    // (word, sentences starting with it, sentences with words)
+    fn top_opener(contents: &str) -> (String, usize, usize) {
+        let doc = Document::new_plain_english_curated(contents);
+        let mut openers: Vec<(String, usize)> = Vec::new();
+        let mut sentences = 0;
+        for sentence in doc.iter_sentences() {
+            let Some(first) = sentence.iter_words().next() else {
+                continue; // links and stray punctuation
+            };
+            sentences += 1;
+            let word = doc.get_span_content_str(&first.span).to_lowercase();
+            match openers.iter_mut().find(|(w, _)| *w == word) {
+                Some((_, count)) => *count += 1,
+                None => openers.push((word, 1)),
+            }
+        }
+        // the first one to reach the top count wins ties
+        let (word, count) = openers
+            .into_iter()
+            .rev()
+            .max_by_key(|(_, count)| *count)
+            .unwrap_or_default();
+        (word, count, sentences)
+    }
+    // Whole-word, case-insensitive matches as (phrase, line, column), 1-based
+    fn phrase_hits(contents: &str, phrases: &[String]) -> Vec<(String, usize, usize)> {
+        let mut hits = Vec::new();
+        for phrase in phrases.iter().filter(|p| !p.trim().is_empty()) {
+            let phrase = phrase.trim();
+            // \b next to punctuation would never match, so only anchor word edges
+            let start = if phrase.starts_with(|c: char| c.is_alphanumeric()) {
+                r"\b"
+            } else {
+                ""
+            };
+            let end = if phrase.ends_with(|c: char| c.is_alphanumeric()) {
+                r"\b"
+            } else {
+                ""
+            };
+            let regex =
+                Regex::new(&format!("(?i){}{}{}", start, regex::escape(phrase), end)).unwrap();
+            for m in regex.find_iter(contents) {
+                let before = &contents[..m.start()];
+                let line = before.matches('\n').count() + 1;
+                let column = before.chars().rev().take_while(|c| *c != '\n').count() + 1;
+                hits.push((phrase.to_string(), line, column));
+            }
+        }
+        hits.sort_by_key(|(_, line, column)| (*line, *column));
+        hits
+    }
+    // The first non-empty line if it passes as a title
+    fn find_title<'a>(contents: &'a str, config: &Config) -> Option<&'a str> {
+        let line = contents.lines().map(|l| l.trim()).find(|l| !l.is_empty())?;
+        if let Some(pattern) = &config.title_pattern {
+            // checked when the config was loaded
+            let regex = Regex::new(pattern).unwrap();
+            return regex.is_match(line).then_some(line);
+        }
+        let words: Vec<&str> = line.split_whitespace().collect();
+        let looks_like_title = config.count_words(WordCount::of_text(line))
+            <= config.title_max_words
+            && !line.ends_with('.')
+            && line.starts_with(|c: char| c.is_uppercase());
+        let capitalized = match config.title_strictness {
+            TitleStrictness::Loose => true,
+            TitleStrictness::Strict => words
+                .iter()
+                .filter(|w| w.chars().count() > 3)
+                .all(|w| w.starts_with(|c: char| c.is_uppercase())),
+        };
+        (looks_like_title && capitalized).then_some(line)
+    }
+    // The line matching closing_pattern: the last non-empty one, or any with closing_anywhere
+    fn find_closing<'a>(contents: &'a str, config: &Config) -> Option<&'a str> {
+        // checked when the config was loaded
+        let regex = Regex::new(config.closing_pattern.as_ref()?).unwrap();
+        let mut lines = contents.lines().map(|l| l.trim()).filter(|l| !l.is_empty());
+        if config.closing_anywhere {
+            lines.find(|l| regex.is_match(l))
+        } else {
+            lines.next_back().filter(|l| regex.is_match(l))
+        }
+    }
+    // Asks the grader at the prompt, None when stdin ends without an answer
+    fn ask_questions() -> Option<Grade> {
+        eprintln!("{}", QUESTIONS_PROMPT);
+        Rubric::read_answer(io::stdin().lock())
+    }
+    // Asks for a comment on the report, None when it is left empty or stdin ends
+    fn ask_comment() -> Option<String> {
+        eprintln!("Comment for the report, empty for none:");
+        let mut line = String::new();
+        io::stdin().read_line(&mut line).ok()?;
+        Some(line.trim().to_string()).filter(|line| !line.is_empty())
+    }
+    // Keeps asking until it gets y, yes, n or no in any case, or a score
+    // from 0 to 100 for partial credit, PROMPT_ATTEMPTS times at most
+    fn read_answer(mut input: impl io::BufRead) -> Option<Grade> {
+        for _ in 0..PROMPT_ATTEMPTS {
+            let mut line = String::new();
+            if input.read_line(&mut line).unwrap_or(0) == 0 {
+                report::warning(
+                    "answers",
+                    "no answer to the questions prompt, leaving the criterion ungraded (see --default-answer)",
+                );
+                return None;
+            }
+            let line = line.trim().trim_end_matches('%').to_lowercase();
+            match line.as_str() {
+                "y" | "yes" => return Some(Grade::new(true)),
+                "n" | "no" => return Some(Grade::new(false)),
+                _ => match line.parse::<f32>() {
+                    Ok(score) if (0.0..=100.0).contains(&score) => {
+                        return Some(Grade::from_percentage(score));
+                    }
+                    _ => eprintln!("Please answer y or n, or give a score from 0 to 100"),
+                },
+            }
+        }
+        report::warning(
+            "answers",
+            &format!(
+                "no valid answer to the questions prompt in {} tries, leaving the criterion ungraded (see --default-answer)",
+                PROMPT_ATTEMPTS
+            ),
+        );
+        None
+    }
+    /// Like `grade_text` with the grader's answer to the questions prompt
+    /// given up front, so nothing is asked or estimated
+    pub fn from_string_with_answers(contents: &str, config: &Config, questions: bool) -> Rubric {
+        let (contents, stripped) = prompt::strip(clean(contents.to_string()), config);
+        let lints = LintRun::new(&contents, config);
+        let mut rubric = Rubric::grade(&contents, config, &lints, Some(Grade::new(questions)));
+        rubric.prompt_stripped = stripped;
+        rubric
+    }
+    /// Like `grade_text` for text that still has to be read: a file, stdin, a
+    /// network response. It has to be UTF-8, a byte order mark and control
+    /// characters other than newlines and tabs are dropped the way the
+    /// command line tool drops them
+    pub fn from_reader<R: io::Read>(reader: R, config: &Config) -> io::Result<Rubric> {
+        let (contents, _) = input::read_text(reader, None)?;
+        Ok(grade_text(&contents, config))
+    }
+    // Whether grading under this config needs the grader's answer
+    fn needs_answer(config: &Config) -> bool {
+        !config.auto_questions
+            && config
+                .categories
+                .iter()
+                .any(|c| c.check == Check::Questions)
+    }
+    // `answer` is the grader's questions answer when it is already known,
+    // otherwise it is estimated (--auto-questions) or asked at the prompt
+    // With `review` the grader dismisses false positives before it is scored
+    fn from_string(
+        contents: String,
+        config: &Config,
+        answer: Option<Grade>,
+        review: bool,
+    ) -> Rubric {
+        let (contents, stripped) = prompt::strip(clean(contents), config);
+        let handle = LintRun::spawn(&contents, config);
+        // ask while harper works, unless the lints might make it pointless
+        let defer = answer.is_none() && config.skip_manual_if_below.is_some();
+        let answer = answer.or_else(|| {
+            (Rubric::needs_answer(config) && !defer)
+                .then(Rubric::ask_questions)
+                .flatten()
+        });
+        let mut lints = LintRun::join(handle);
+        if review {
+            lints.review(config);
+        }
+        let mut rubric = match answer {
+            None if defer => Rubric::skip_manual(&contents, config, &lints).unwrap_or_else(|| {
+                let answer = Rubric::needs_answer(config)
+                    .then(Rubric::ask_questions)
+                    .flatten();
+                Rubric::grade(&contents, config, &lints, answer)
+            }),
+            _ => Rubric::grade(&contents, config, &lints, answer),
+        };
+        rubric.prompt_stripped = stripped;
+        rubric
+    }
+    // --skip-manual-if-below: when even a yes would leave the final score
+    // short of the minimum asking is pointless, and the questions criterion
+    // is left ungraded with that best case in its detail. None when the
+    // prompt is still worth it
+    fn skip_manual(contents: &str, config: &Config, lints: &LintRun) -> Option<Rubric> {
+        let min = config.skip_manual_if_below?;
+        if !Rubric::needs_answer(config) {
+            return None;
+        }
+        let mut rubric = Rubric::grade(contents, config, lints, Some(Grade::new(true)));
+        let best = rubric.score_percent();
+        if best >= min {
+            return None;
+        }
+        log::info!("skipping the questions prompt, {}% at most", best);
+        let skipped: Vec<String> = rubric.manual.drain(..).map(|m| m.criterion).collect();
+        for criterion in &mut rubric.criteria {
+            if skipped.contains(&criterion.name) {
+                criterion.grade = Grade::empty();
+                criterion.detail = format!(
+                    "not asked, the final score is {}% at most with it, {}% needed",
+                    best, min
+                );
+            }
+        }
+        Some(rubric)
+    }
+    // Scores already linted contents, `answer` as in from_string except it
+    // is never asked for
+    fn grade(contents: &str, config: &Config, lints: &LintRun, answer: Option<Grade>) -> Rubric {
+        let start = Instant::now();
+        let mut out = Rubric {
+            lint_failure: lints.failure.clone(),
+            dismissed: lints.dismissed.clone(),
+            failed_rules: lints.failed_rules.clone(),
+            diagnostics: Diagnostics {
+                lint_ms: lints.elapsed.as_secs_f64() * 1000.0,
+                lints: lints.errors.len() + lints.dismissed.len(),
+                per_category: BTreeMap::new(),
+            },
+            ..Default::default()
+        };
+        out.unevaluated = config.unevaluated;
+        out.rounding = config.rounding;
+        let mut extra = Vec::new();
+        for (check, info) in config.extras() {
+            if !info.enabled {
+                continue;
+            }
+            let (grade, detail) = out.grade_extra(check, contents, config, lints);
+            extra.push(Criterion {
+                name: info.name,
+                description: info.description,
+                grade,
+                weight: info.weight,
+                warning: false,
+                detail,
+            });
+        }
+        let mut questions = Grade::empty();
+        let mut estimated = false;
+        if let Some(answer) = answer {
+            questions = answer;
+        } else if config.auto_questions {
+            questions = Grade::new(Rubric::complete_sentences(contents));
+            estimated = true;
+        }
+        let errors = out.countable_errors(lints, config);
+        let words = config.count_words(lints.words);
+        for category in &config.categories {
+            if category.check == Check::Questions
+                && let Some(answer) = questions.val
+            {
+                out.manual.push(ManualAnswer {
+                    criterion: category.name.clone(),
+                    prompt: QUESTIONS_PROMPT.to_string(),
+                    answer: answer >= 1.0,
+                    score: round_percent(answer * 100.0),
+                    estimated,
+                });
+            }
+            let (grade, detail) = match category.check {
+                Check::Link => Rubric::grade_link(contents, config),
+                Check::Questions => (questions, Rubric::questions_detail(questions, estimated)),
+                Check::Lints if out.lint_failure.is_some() => (Grade::new(false), String::new()),
+                Check::Lints => out.grade_lints(category, &errors, words, config),
+            };
+            out.criteria.push(Criterion {
+                name: category.name.clone(),
+                description: category.description.clone(),
+                grade,
+                weight: category.weight,
+                detail,
+                warning: category.severity == Severity::Warn,
+            });
+        }
+        out.criteria.extend(extra);
+        for c in &out.criteria {
+            log::debug!("{}: {:?} ({})", c.name, c.grade.val, c.detail);
+        }
+        out.errors = errors;
+        profile::record("score", start.elapsed());
+        out
+    }
+    // The mistakes that count against a category, each one tagged with it.
+    // What list_exemption, british_spellings and spelling_ignore let through
+    // is noted on the rubric instead
+    fn countable_errors(&mut self, lints: &LintRun, config: &Config) -> Vec<LintError> {
+        let mut errors = lints.errors.clone();
+        errors.retain(|e| {
+            let exempt = e.list_item && config.list_exemption.exempts(e.category);
+            if exempt {
+                log::debug!("list item exempt: '{}': {}", e.text, e.message);
+            }
+            !exempt
+        });
+        errors.retain(|e| {
+            let accepted = e.british && config.british_spellings != BritishSpellings::Mistakes;
+            if accepted {
+                log::debug!("british_spellings: '{}'", e.text);
+                self.british_accepted.push(e.text.clone());
+            }
+            !accepted
+        });
+        errors.retain(|e| {
+            let ignored = e.category == Some(LintCategory::Spelling)
+                && config.spelling_ignore.iter().any(|t| t.matches(&e.text));
+            if ignored {
+                log::debug!("spelling_ignore: '{}'", e.text);
+                self.ignored_tokens.push(e.text.clone());
+            }
+            !ignored
+        });
+        // each mistake counts against one category at most
+        errors.retain_mut(|e| match config.category_for(e.kind, e.category) {
+            Some(category) => {
+                e.criterion = category.name.clone();
+                if config.early_error_weight.is_some() {
+                    let weight = config.error_weight(e.position);
+                    e.weight = Some((weight * 100.0).round() / 100.0);
+                }
+                true
+            }
+            None => false,
+        });
+        errors
+    }
+    fn grade_link(contents: &str, config: &Config) -> (Grade, String) {
+        let forbidden = Rubric::links_to(contents, &config.forbidden_link_domains);
+        if !forbidden.is_empty() {
+            return (
+                Grade::new(false),
+                format!("forbidden: {}", forbidden.join(", ")),
+            );
+        }
+        // nothing to look for without domains
+        if config.link_domains.is_empty() {
+            return (Grade::empty(), "not evaluated".to_string());
+        }
+        let (links, pasted) = Rubric::good_links(contents, &config.link_domains);
+        let grade = Grade::from_ratio(links, config.min_links);
+        let detail = if pasted > links {
+            format!(
+                "{} of {} links, {} unique of {} pasted",
+                links.min(config.min_links),
+                config.min_links,
+                links,
+                pasted
+            )
+        } else if config.min_links > 1 {
+            format!(
+                "{} of {} links",
+                links.min(config.min_links),
+                config.min_links
+            )
+        } else {
+            String::new()
+        };
+        (grade, detail)
+    }
+    fn questions_detail(questions: Grade, estimated: bool) -> String {
+        match questions.val {
+            None => "not answered".to_string(),
+            Some(_) if estimated => "estimated".to_string(),
+            Some(0.0 | 1.0) => String::new(),
+            Some(_) => format!("scored {}%", round_percent(questions.perc() * 100.0)),
+        }
+    }
+    // A lint category passes while its mistakes (weighed under
+    // early_error_weight) stay within what its rate and tolerance allow
+    fn grade_lints(
+        &mut self,
+        category: &Category,
+        errors: &[LintError],
+        words: usize,
+        config: &Config,
+    ) -> (Grade, String) {
+        let counted: Vec<&LintError> = errors
+            .iter()
+            .filter(|e| e.criterion == category.name)
+            .collect();
+        let found = counted.len();
+        self.diagnostics
+            .per_category
+            .insert(category.name.clone(), found);
+        // each mistake counts once unless early_error_weight says otherwise
+        let weighed: f32 = counted.iter().map(|e| e.weight.unwrap_or(1.0)).sum();
+        let found_text = if config.early_error_weight.is_some() && found > 0 {
+            format!("{} found weighing {:.1}", found, weighed)
+        } else {
+            format!("{} found", found)
+        };
+        let allowed = (words as f32 * category.rate).ceil() as usize + category.tolerance;
+        let detail = if allowed > 0 {
+            format!("{}, {} allowed", found_text, allowed)
+        } else if category.severity == Severity::Warn {
+            // the score doesn't show it, so say how many
+            found_text
+        } else {
+            String::new()
+        };
+        (Grade::new(weighed <= allowed as f32), detail)
+    }
+    // One of the optional criteria, see Config::extras for what turns it on
+    fn grade_extra(
+        &mut self,
+        extra: Extra,
+        contents: &str,
+        config: &Config,
+        lints: &LintRun,
+    ) -> (Grade, String) {
+        match extra {
+            Extra::Paragraphs => Rubric::grade_paragraphs(contents, config),
+            Extra::Transitions => Rubric::grade_transitions(contents, config),
+            Extra::SentenceLength => Rubric::grade_sentence_length(contents, config),
+            Extra::AnswerSentences => Rubric::grade_answer_sentences(contents, config),
+            Extra::ForbiddenWords => Rubric::grade_forbidden_words(contents, config),
+            Extra::Title => Rubric::grade_title(contents, config),
+            Extra::Closing => Rubric::grade_closing(contents, config),
+            Extra::Readability => {
+                let stats = Readability::measure(contents, config);
+                let graded = Rubric::grade_readability(&stats, config);
+                self.readability = Some(stats);
+                graded
+            }
+            Extra::Tense => Rubric::grade_tense(contents, config),
+            Extra::Openers => Rubric::grade_openers(contents, config),
+            Extra::ParagraphBalance => Rubric::grade_paragraph_balance(contents, config),
+            Extra::Filler => Rubric::grade_filler(contents, config),
+            Extra::Voice => Rubric::grade_voice(contents, config),
+            Extra::CompleteParagraphs => Rubric::grade_complete_paragraphs(contents),
+            Extra::Quotes => Rubric::grade_quotes(contents),
+            Extra::ConsistentSpelling => Rubric::grade_consistent_spelling(lints),
+            Extra::Keywords(i) => Rubric::grade_keywords(contents, &config.keyword_sets[i]),
+            Extra::AnswerKey => Rubric::grade_answer_key(contents, config),
+        }
+    }
+    fn grade_paragraphs(contents: &str, config: &Config) -> (Grade, String) {
+        let paragraphs = Rubric::count_paragraphs(contents);
+        (
+            Grade::new(paragraphs >= config.min_paragraphs),
+            format!("{} found", paragraphs),
+        )
+    }
+    fn grade_transitions(contents: &str, config: &Config) -> (Grade, String) {
+        let hits = Rubric::phrase_hits(contents, &config.transition_words);
+        // in the order they're first used
+        let mut found: Vec<&str> = Vec::new();
+        for (phrase, _, _) in &hits {
+            if !found.contains(&phrase.as_str()) {
+                found.push(phrase);
+            }
+        }
+        (
+            Grade::from_ratio(found.len(), config.min_transitions),
+            if found.is_empty() {
+                "none found".to_string()
+            } else {
+                format!("{} found: {}", found.len(), found.join(", "))
+            },
+        )
+    }
+    fn grade_sentence_length(contents: &str, config: &Config) -> (Grade, String) {
+        let long = Rubric::long_sentences(contents, config);
+        (
+            Grade::new(long <= config.max_long_sentences),
+            format!("{} found", long),
+        )
+    }
+    fn grade_answer_sentences(contents: &str, config: &Config) -> (Grade, String) {
+        let answers = Rubric::answers(contents, config.split_on.as_deref());
+        let thin: Vec<String> = answers
+            .iter()
+            .map(|(label, text)| (label, Rubric::distinct_sentences(text)))
+            .filter(|(_, sentences)| *sentences < config.min_sentences_per_answer)
+            .map(|(label, sentences)| format!("'{}' has {}", label, sentences))
+            .collect();
+        (
+            Grade::new(thin.is_empty()),
+            if thin.is_empty() {
+                format!("{} answers", answers.len())
+            } else {
+                format!(
+                    "{} of {} answers short: {}",
+                    thin.len(),
+                    answers.len(),
+                    thin.join(", ")
+                )
+            },
+        )
+    }
+    fn grade_forbidden_words(contents: &str, config: &Config) -> (Grade, String) {
+        let hits = Rubric::phrase_hits(contents, &config.forbidden_words);
+        let detail = if hits.is_empty() {
+            "none found".to_string()
+        } else {
+            hits.iter()
+                .map(|(phrase, line, column)| format!("'{}' at {}:{}", phrase, line, column))
+                .collect::<Vec<_>>()
+                .join(", ")
+        };
+        (Grade::new(hits.is_empty()), detail)
+    }
+    fn grade_title(contents: &str, config: &Config) -> (Grade, String) {
+        let title = Rubric::find_title(contents, config);
+        (
+            Grade::new(title.is_some()),
+            match title {
+                Some(title) => format!("found '{}'", title),
+                None => "no title found".to_string(),
+            },
+        )
+    }
+    fn grade_closing(contents: &str, config: &Config) -> (Grade, String) {
+        let closing = Rubric::find_closing(contents, config);
+        (
+            Grade::new(closing.is_some()),
+            match closing {
+                Some(line) => format!("found '{}'", line),
+                None => format!(
+                    "no line matches '{}'",
+                    config.closing_pattern.as_deref().unwrap_or_default()
+                ),
+            },
+        )
+    }
+    fn grade_readability(stats: &Readability, config: &Config) -> (Grade, String) {
+        let in_range = config
+            .min_grade_level
+            .is_none_or(|min| stats.grade_level >= min)
+            && config
+                .max_grade_level
+                .is_none_or(|max| stats.grade_level <= max);
+        (
+            Grade::new(in_range),
+            format!(
+                "grade level {:.1}, reading ease {:.1}",
+                stats.grade_level, stats.reading_ease
+            ),
+        )
+    }
+    fn grade_tense(contents: &str, config: &Config) -> (Grade, String) {
+        let (past, present) = Rubric::verb_tenses(contents);
+        let mix = past.min(present) as f32 / (past + present).max(1) as f32;
+        (
+            Grade::new(mix <= config.max_tense_mix.unwrap_or_default()),
+            format!(
+                "{} past, {} present verbs, {}% in the other tense",
+                past,
+                present,
+                round_percent(mix * 100.0)
+            ),
+        )
+    }
+    fn grade_openers(contents: &str, config: &Config) -> (Grade, String) {
+        let (word, count, sentences) = Rubric::top_opener(contents);
+        let share = count as f32 / sentences.max(1) as f32;
+        // too few sentences to call it monotonous
+        if sentences < MIN_OPENER_SENTENCES {
+            return (Grade::new(true), format!("only {} sentences", sentences));
+        }
+        (
+            Grade::new(share <= config.max_same_opener.unwrap_or_default()),
+            format!("'{}' starts {} of {} sentences", word, count, sentences),
+        )
+    }
+    fn grade_paragraph_balance(contents: &str, config: &Config) -> (Grade, String) {
+        let paragraphs = Rubric::paragraph_words(contents, config);
+        let longest = paragraphs.iter().copied().max().unwrap_or_default();
+        let shortest = paragraphs.iter().copied().min().unwrap_or_default();
+        let share = longest as f32 / paragraphs.iter().sum::<usize>().max(1) as f32;
+        (
+            Grade::new(share <= config.max_paragraph_share.unwrap_or_default()),
+            if paragraphs.is_empty() {
+                "no paragraphs".to_string()
+            } else {
+                format!(
+                    "longest {} words ({}% of the words), shortest {} words",
+                    longest,
+                    round_percent(share * 100.0),
+                    shortest
+                )
+            },
+        )
+    }
+    fn grade_filler(contents: &str, config: &Config) -> (Grade, String) {
+        let hits = Rubric::phrase_hits(contents, &config.filler_phrases);
+        let words = config.count_words(WordCount::of_text(contents));
+        let density = hits.len() as f32 * 100.0 / words.max(1) as f32;
+        // each phrase once, with how often it came up
+        let mut counts: Vec<(&str, usize)> = Vec::new();
+        for (phrase, _, _) in &hits {
+            match counts.iter_mut().find(|(p, _)| p == phrase) {
+                Some((_, n)) => *n += 1,
+                None => counts.push((phrase, 1)),
+            }
+        }
+        let phrases: Vec<String> = counts
+            .iter()
+            .map(|(phrase, n)| match n {
+                1 => format!("'{}'", phrase),
+                n => format!("'{}' x{}", phrase, n),
+            })
+            .collect();
+        (
+            Grade::new(density <= config.max_filler_density.unwrap_or_default()),
+            if hits.is_empty() {
+                "none found".to_string()
+            } else {
+                format!(
+                    "{} in {} words ({:.1} per 100): {}",
+                    hits.len(),
+                    words,
+                    density,
+                    phrases.join(", ")
+                )
+            },
+        )
+    }
+    fn grade_voice(contents: &str, config: &Config) -> (Grade, String) {
+        let pronouns = Rubric::first_person(contents);
+        // each once, as first written
+        let mut distinct: Vec<&str> = Vec::new();
+        for pronoun in &pronouns {
+            if !distinct.iter().any(|d| d.eq_ignore_ascii_case(pronoun)) {
+                distinct.push(pronoun);
+            }
+        }
+        (
+            Grade::new(match config.voice {
+                Some(Voice::First) => !pronouns.is_empty(),
+                _ => pronouns.len() <= config.max_first_person,
+            }),
+            if pronouns.is_empty() {
+                "no first-person pronouns".to_string()
+            } else {
+                format!(
+                    "{} first-person pronouns ({})",
+                    pronouns.len(),
+                    distinct.join(", ")
+                )
+            },
+        )
+    }
+    fn grade_complete_paragraphs(contents: &str) -> (Grade, String) {
+        let incomplete = Rubric::incomplete_paragraphs(contents);
+        (
+            Grade::new(incomplete.is_empty()),
+            match incomplete.as_slice() {
+                [] => String::new(),
+                [paragraph] => format!("paragraph {} has none", paragraph),
+                paragraphs => format!(
+                    "paragraphs {} have none",
+                    paragraphs
+                        .iter()
+                        .map(|p| p.to_string())
+                        .collect::<Vec<_>>()
+                        .join(", ")
+                ),
+            },
+        )
+    }
+    fn grade_quotes(contents: &str) -> (Grade, String) {
+        let unclosed = Rubric::unclosed_quotes(contents);
+        (
+            Grade::new(unclosed.is_empty()),
+            if unclosed.is_empty() {
+                String::new()
+            } else {
+                let at: Vec<String> = unclosed
+                    .iter()
+                    .map(|(line, column)| format!("{}:{}", line, column))
+                    .collect();
+                format!("unclosed at {}", at.join(", "))
+            },
+        )
+    }
+    fn grade_consistent_spelling(lints: &LintRun) -> (Grade, String) {
+        let british = Rubric::distinct(lints.errors.iter().filter(|e| e.british).map(|e| &e.text));
+        let american = Rubric::distinct(lints.american.iter());
+        (
+            Grade::new(british.is_empty() || american.is_empty()),
+            match (british.is_empty(), american.is_empty()) {
+                (false, false) => format!(
+                    "British {} and American {}",
+                    british.join(", "),
+                    american.join(", ")
+                ),
+                (false, true) => "British".to_string(),
+                (true, false) => "American".to_string(),
+                (true, true) => String::new(),
+            },
+        )
+    }
+    // What was found and what's missing, for keyword sets and the answer key
+    fn found_and_missing(found: &[&str], missing: &[&str]) -> String {
+        match (found.is_empty(), missing.is_empty()) {
+            (true, _) => format!("missing: {}", missing.join(", ")),
+            (false, true) => format!("found: {}", found.join(", ")),
+            (false, false) => format!(
+                "found: {}; missing: {}",
+                found.join(", "),
+                missing.join(", ")
+            ),
+        }
+    }
+    fn grade_keywords(contents: &str, set: &KeywordSet) -> (Grade, String) {
+        let hits = Rubric::phrase_hits(contents, &set.keywords);
+        let (found, missing): (Vec<&str>, Vec<&str>) = set
+            .keywords
+            .iter()
+            .map(|k| k.as_str())
+            .partition(|k| hits.iter().any(|(hit, _, _)| hit == k.trim()));
+        (
+            Grade::from_ratio(found.len(), set.needed()),
+            Rubric::found_and_missing(&found, &missing),
+        )
+    }
+    fn grade_answer_key(contents: &str, config: &Config) -> (Grade, String) {
+        let (found, missing): (Vec<&KeyAnswer>, Vec<&KeyAnswer>) = config
+            .expected_answers
+            .iter()
+            .partition(|a| a.found_in(contents));
+        let found: Vec<&str> = found.iter().map(|a| a.label.as_str()).collect();
+        let missing: Vec<&str> = missing.iter().map(|a| a.label.as_str()).collect();
+        (
+            Grade::from_ratio(found.len(), config.expected_answers.len()),
+            Rubric::found_and_missing(&found, &missing),
+        )
+    }
+    fn output(&self, options: &ReportOptions) -> String {
+        template::render(template::DEFAULT_TEMPLATE, self, options)
+            .expect("default template is broken")
+    }
+    // The text report, rendered with --template when there is one
+    fn output_text(&self, template: Option<&str>, options: &ReportOptions) -> String {
+        let report = match template {
+            Some(text) => template::render(text, self, options).unwrap_or_else(|e| {
+                report::fatal("template", &format!("failed to render template: {}", e))
+            }),
+            None => self.output(options),
+        };
+        if options.explain_score {
+            format!(
+                "{}\n{}\n",
+                report.trim_end_matches('\n'),
+                self.explain_score()
+            )
+        } else {
+            report
+        }
+    }
+    fn json_report<'a>(
+        &'a self,
+        options: &'a ReportOptions<'a>,
+        file: Option<&'a str>,
+    ) -> JsonReport<'a> {
+        JsonReport {
+            schema_version: SCHEMA_VERSION,
+            file,
+            config_file: None,
+            metadata: &options.metadata,
+            score: self.score_fraction(),
+            rubric: self,
+            config: options.config,
+            config_hash: options.config.map(|c| c.hash()),
+        }
+    }
+    fn output_json(&self, options: &ReportOptions) -> String {
+        serde_json::to_string_pretty(&self.json_report(options, None))
+            .expect("failed to serialize rubric")
+    }
+    // The assignment columns are only there when some metadata was given
+    fn csv_header(&self, options: &ReportOptions) -> String {
+        let mut fields = vec!["file".to_string()];
+        if !options.metadata.is_empty() {
+            fields.extend(["assignment", "due_date", "grader"].map(String::from));
+        }
+        fields.push("score".to_string());
+        fields.extend(self.criteria.iter().map(|c| csv_field(&c.name)));
+        fields.join(",")
+    }
+    // Scores in percent, criteria as the share of themselves they earned
+    fn csv_row(&self, options: &ReportOptions, file: &str) -> String {
+        let mut fields = vec![csv_field(file)];
+        let metadata = &options.metadata;
+        if !metadata.is_empty() {
+            fields.extend(
+                [&metadata.assignment, &metadata.due_date, &metadata.grader]
+                    .map(|value| csv_field(value.as_deref().unwrap_or(""))),
+            );
+        }
+        fields.push(self.score_percent().to_string());
+        fields.extend(
+            self.criteria
+                .iter()
+                .map(|c| self.rounding.apply(c.grade.perc() * 100.0).to_string()),
+        );
+        fields.join(",")
+    }
+    fn output_jsonl(&self, options: &ReportOptions, file: &str) -> String {
+        serde_json::to_string(&self.json_report(options, Some(file)))
+            .expect("failed to serialize rubric")
+    }
+}
+#[cfg(feature = "pdf")]
+fn write_pdf(rubric: &Rubric, options: &ReportOptions, path: &std::path::Path) {
+    match pdf::write(rubric, &options.metadata, path) {
+        Ok(()) => report::note(&format!("wrote {}", path.display())),
+        Err(e) => report::fatal("pdf", &e),
+    }
+}
+#[cfg(not(feature = "pdf"))]
+fn write_pdf(_: &Rubric, _: &ReportOptions, _: &std::path::Path) {
+    unreachable!("checked before grading");
+}
+#[cfg(feature = "sqlite")]
+fn store_grades(
+    path: &std::path::Path,
+    assignment: &str,
+    graded: &[(String, Rubric)],
+) -> Result<(), String> {
+    db::store(path, assignment, graded)?;
+    report::note(&format!(
+        "stored {} grades in {}",
+        graded.len(),
+        path.display()
+    ));
+    Ok(())
+}
+#[cfg(not(feature = "sqlite"))]
+fn store_grades(_: &std::path::Path, _: &str, _: &[(String, Rubric)]) -> Result<(), String> {
+    unreachable!("checked before grading");
+}
+
+#[cfg(feature = "server")]
+fn serve(host: &str, port: u16, config: &Config, options: &ReportOptions) {
+    if let Err(e) = server::serve(host, port, config, options) {
+        report::fatal("server", &e);
+    }
+}
+#[cfg(not(feature = "server"))]
+fn serve(_: &str, _: u16, _: &Config, _: &ReportOptions) {
+    unreachable!("checked before serving");
+}
+
+// Lints each submission once and scores it under both configs, asking for
+// the questions answer at most once per file
+fn diff_scores(
+    sources: Vec<input::Source>,
+    reading: input::Reading,
+    config: &Config,
+    other: &Config,
+    answer_for: impl Fn(&str) -> Option<Grade>,
+) -> (Vec<diff::Row>, bool) {
+    let mut rows = Vec::new();
+    let mut failed = false;
+    for source in sources {
+        let submission = match source.read(reading) {
+            Ok(submission) => submission,
+            Err(e) => {
+                report::error("input", &e);
+                failed = true;
+                continue;
+            }
+        };
+        // the current config decides which rules run, whether links are
+        // masked and the prompt left out for both
+        let (contents, _) = prompt::strip(clean(submission.contents), config);
+        let lints = LintRun::new(&contents, config);
+        let mut answer = answer_for(&submission.name);
+        let [before, after] = [config, other].map(|c| match answer {
+            None => Rubric::skip_manual(&contents, c, &lints),
+            Some(_) => None,
+        });
+        if answer.is_none()
+            && ((Rubric::needs_answer(config) && before.is_none())
+                || (Rubric::needs_answer(other) && after.is_none()))
+        {
+            report::note(&format!("== {} ==", submission.name));
+            answer = Rubric::ask_questions();
+        }
+        let score = |skipped: Option<Rubric>, config: &Config| match (answer, skipped) {
+            (None, Some(rubric)) => rubric.score_percent(),
+            _ => Rubric::grade(&contents, config, &lints, answer).score_percent(),
+        };
+        rows.push(diff::Row {
+            before: score(before, config),
+            after: score(after, other),
+            file: submission.name,
+        });
+    }
+    (rows, failed)
+}
+
+// A submission read, and graded already when it was graded in parallel
+type Submitted = Result<(input::Submission, Option<Rubric>), String>;
+
+// --jobs: reads and grades the sources on `jobs` threads, never prompting,
+// and hands them back in source order as soon as each one and those before
+// it are done
+fn grade_in_parallel(
+    sources: Vec<(input::Source, Option<Grade>)>,
+    jobs: usize,
+    reading: input::Reading,
+    config: &Config,
+) -> impl Iterator<Item = Submitted> + use<> {
+    let (sender, receiver) = std::sync::mpsc::channel();
+    let queue = Arc::new(std::sync::Mutex::new(sources.into_iter().enumerate()));
+    let config = Arc::new(config.clone());
+    for _ in 0..jobs {
+        let (queue, config, sender) = (queue.clone(), config.clone(), sender.clone());
+        std::thread::spawn(move || {
+            loop {
+                // a worker that panicked holding the queue left it as it was
+                let next = queue.lock().unwrap_or_else(|e| e.into_inner()).next();
+                let Some((i, (source, answer))) = next else {
+                    break;
+                };
+                // a panic still sends this file back, as an error, so the
+                // ones after it aren't held up and the run fails
+                let name = source.name();
+                let read = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+                    source.read(reading).map(|mut submission| {
+                        let contents = std::mem::take(&mut submission.contents);
+                        let rubric = Rubric::from_string(contents, &config, answer, false);
+                        (submission, Some(rubric))
+                    })
+                }))
+                .unwrap_or_else(|panic| {
+                    Err(format!(
+                        "grading {} failed: {}",
+                        name,
+                        panic_reason(panic.as_ref())
+                    ))
+                });
+                if sender.send((i, read)).is_err() {
+                    break;
+                }
+            }
+        });
+    }
+    drop(sender);
+    let mut done: BTreeMap<usize, Submitted> = BTreeMap::new();
+    let mut next = 0;
+    std::iter::from_fn(move || {
+        loop {
+            if let Some(read) = done.remove(&next) {
+                next += 1;
+                return Some(read);
+            }
+            let (i, read) = receiver.recv().ok()?;
+            done.insert(i, read);
+        }
+    })
+}
+
+// One --lint-only finding
+#[derive(Serialize)]
+struct Issue<'a> {
+    line: usize,
+    column: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    category: Option<LintCategory>,
+    kind: LintKind,
+    text: &'a str,
+    message: &'a str,
+    suggestions: &'a [String],
+}
+impl<'a> From<&'a LintError> for Issue<'a> {
+    fn from(e: &'a LintError) -> Issue<'a> {
+        Issue {
+            line: e.line,
+            column: e.column,
+            category: e.category,
+            kind: e.kind,
+            text: &e.text,
+            message: &e.message,
+            suggestions: &e.suggestions,
+        }
+    }
+}
+#[derive(Serialize)]
+struct LintReport<'a> {
+    file: &'a str,
+    issues: Vec<Issue<'a>>,
+}
+
+// --dry-run: the inputs read and the dictionary loaded, the configs,
+// template, baseline, answers and comments were already loaded by now. Urls
+// aren't fetched and pipes aren't opened, reading them would use them up
+fn dry_run(sources: &[input::Source], args: &Args) -> bool {
+    let mut failed = false;
+    match std::panic::catch_unwind(|| FstDictionary::curated().word_count()) {
+        Ok(0) | Err(_) => {
+            report::error("lint", "harper's dictionary didn't load");
+            failed = true;
+        }
+        Ok(words) => log::debug!("dictionary: {} words", words),
+    }
+    let reading = input::Reading::of(args);
+    for source in sources {
+        match source {
+            input::Source::Url(url) => report::note(&format!("{}: not fetched", url)),
+            input::Source::File(path) if input::is_fifo(path) => {
+                report::note(&format!("{}: named pipe, not opened", path.display()))
+            }
+            _ => {
+                if let Err(e) = source.read(reading) {
+                    report::error("input", &e);
+                    failed = true;
+                }
+            }
+        }
+    }
+    failed
+}
+
+// --lint-only: everything harper finds in each submission, nothing graded or
+// asked. Text is a `file:line:column: message` line per issue, json an object
+// (an array of them in batch mode) and jsonl one per file
+fn lint_only(sources: Vec<input::Source>, args: &Args, config: &Config) -> bool {
+    let batch = input::is_batch(args);
+    let mut failed = false;
+    let mut linted: Vec<(String, LintRun)> = Vec::new();
+    for source in sources {
+        let submission = match source.read(input::Reading::of(args)) {
+            Ok(submission) => submission,
+            Err(e) => {
+                report::error("input", &e);
+                failed = true;
+                continue;
+            }
+        };
+        let contents = clean(submission.contents);
+        let lints = LintRun::new(&contents, config);
+        if let Some(failure) = &lints.failure {
+            report::error("lint", &format!("{}: {}", submission.name, failure));
+            failed = true;
+        }
+        match args.format {
+            Format::Text => {
+                for e in &lints.errors {
+                    let mut line = format!(
+                        "{}:{}:{}: '{}': {}",
+                        submission.name, e.line, e.column, e.text, e.message
+                    );
+                    if !e.suggestions.is_empty() {
+                        line += &format!(" ({})", e.suggestions.join("; "));
+                    }
+                    println!("{}", line);
+                }
+            }
+            Format::Jsonl => {
+                let report = LintReport {
+                    file: &submission.name,
+                    issues: lints.errors.iter().map(Issue::from).collect(),
+                };
+                println!(
+                    "{}",
+                    serde_json::to_string(&report).expect("failed to serialize lints")
+                );
+            }
+            _ => linted.push((submission.name, lints)),
+        }
+    }
+    if args.format == Format::Json {
+        let reports: Vec<LintReport> = linted
+            .iter()
+            .map(|(file, lints)| LintReport {
+                file,
+                issues: lints.errors.iter().map(Issue::from).collect(),
+            })
+            .collect();
+        let json = match reports.as_slice() {
+            [report] if !batch => serde_json::to_string_pretty(report),
+            _ => serde_json::to_string_pretty(&reports),
+        };
+        println!("{}", json.expect("failed to serialize lints"));
+    }
+    failed
+}
+
+// Lints each submission once and scores it under every --config, asking
+// for the questions answer at most once per file. Text reports come under a
+// heading per config, json and jsonl reports carry `config_file` and csv
+// has a final score column per config
+fn grade_rubrics(
+    sources: Vec<input::Source>,
+    args: &Args,
+    rubrics: &[(String, Config)],
+    template: Option<&str>,
+    answer_for: impl Fn(&str) -> Option<Grade>,
+    comment_for: impl Fn(&str) -> Option<String>,
+) -> bool {
+    let batch = input::is_batch(args);
+    let options: Vec<ReportOptions> = rubrics
+        .iter()
+        .map(|(_, config)| report_options(args, config))
+        .collect();
+    let mut failed = false;
+    let mut graded: Vec<(String, usize, Rubric)> = Vec::new();
+    if args.format == Format::Csv {
+        let labels = rubrics.iter().map(|(label, _)| csv_field(label));
+        println!("file,{}", labels.collect::<Vec<_>>().join(","));
+    }
+    for source in sources {
+        let submission = match source.read(input::Reading::of(args)) {
+            Ok(submission) => submission,
+            Err(e) => {
+                report::error("input", &e);
+                failed = true;
+                continue;
+            }
+        };
+        if batch {
+            match args.format {
+                Format::Text => println!("== {} ==", submission.name),
+                _ => report::note(&format!("== {} ==", submission.name)),
+            }
+        }
+        // the first config decides which rules run, whether links are masked
+        // and the prompt left out for all of them
+        let (contents, stripped) = prompt::strip(clean(submission.contents), &rubrics[0].1);
+        let lints = LintRun::new(&contents, &rubrics[0].1);
+        let mut answer = answer_for(&submission.name);
+        let mut skipped: Vec<Option<Rubric>> = rubrics
+            .iter()
+            .map(|(_, c)| match answer {
+                None => Rubric::skip_manual(&contents, c, &lints),
+                Some(_) => None,
+            })
+            .collect();
+        if answer.is_none()
+            && rubrics
+                .iter()
+                .zip(&skipped)
+                .any(|((_, c), skip)| Rubric::needs_answer(c) && skip.is_none())
+        {
+            answer = Rubric::ask_questions();
+        }
+        let comment = comment_for(&submission.name);
+        let mut scores = vec![csv_field(&submission.name)];
+        for (i, (label, config)) in rubrics.iter().enumerate() {
+            let mut rubric = match (answer, skipped[i].take()) {
+                (None, Some(rubric)) => rubric,
+                _ => Rubric::grade(&contents, config, &lints, answer),
+            };
+            rubric.comment = comment.clone();
+            rubric.prompt_stripped = stripped.clone();
+            match args.format {
+                Format::Text => {
+                    println!("-- {} --", label);
+                    println!("{}", rubric.output_text(template, &options[i]));
+                }
+                Format::Jsonl => {
+                    let report = JsonReport {
+                        config_file: Some(label),
+                        ..rubric.json_report(&options[i], Some(&submission.name))
+                    };
+                    println!(
+                        "{}",
+                        serde_json::to_string(&report).expect("failed to serialize rubric")
+                    );
+                }
+                Format::Csv => scores.push(rubric.score_percent().to_string()),
+                Format::Json => graded.push((submission.name.clone(), i, rubric)),
+                Format::Pdf => unreachable!("checked before grading"),
+            }
+        }
+        if args.format == Format::Csv {
+            println!("{}", scores.join(","));
+        }
+    }
+    if args.format == Format::Json {
+        let reports: Vec<JsonReport> = graded
+            .iter()
+            .map(|(name, i, rubric)| JsonReport {
+                config_file: Some(&rubrics[*i].0),
+                ..rubric.json_report(&options[*i], Some(name))
+            })
+            .collect();
+        println!(
+            "{}",
+            serde_json::to_string_pretty(&reports).expect("failed to serialize rubric")
+        );
+    }
+    failed
+}
+
+// sections in the config: the files named graded section by section, each
+// section's report printed first in text mode, then their weighted total as
+// the one submission
+fn grade_sections_of(
+    sources: Vec<input::Source>,
+    args: &Args,
+    config: &Config,
+    template: Option<&str>,
+    options: &ReportOptions,
+    answer_for: impl Fn(&str) -> Option<Grade>,
+) -> Submitted {
+    let names: Vec<String> = sources.iter().map(|s| s.name()).collect();
+    let (assigned, unmatched) = sections::assign(&config.sections, &names);
+    for &i in &unmatched {
+        report::warning(
+            "sections",
+            &format!("{} matches no section, left out", names[i]),
+        );
+    }
+    let mut sources: Vec<Option<input::Source>> = sources.into_iter().map(Some).collect();
+    let mut graded = Vec::new();
+    let mut texts = Vec::new();
+    let mut read: Vec<String> = Vec::new();
+    for (section, matched) in config.sections.iter().zip(assigned) {
+        let mut parts: Vec<input::Source> =
+            matched.iter().filter_map(|&i| sources[i].take()).collect();
+        let source = match parts.len() {
+            0 => {
+                report::warning(
+                    "sections",
+                    &format!(
+                        "section {}: no file matches {}",
+                        section.name, section.files
+                    ),
+                );
+                graded.push(SectionGrade::new(section, Vec::new(), None));
+                continue;
+            }
+            1 => parts.remove(0),
+            _ => input::Source::Aggregate(parts),
+        };
+        let submission = source.read(input::Reading::of(args))?;
+        match args.format {
+            Format::Text => println!("== section {}: {} ==", section.name, submission.name),
+            _ => report::note(&format!(
+                "== section {}: {} ==",
+                section.name, submission.name
+            )),
+        }
+        let answer = answer_for(&submission.name);
+        let rubric = Rubric::from_string(submission.contents.clone(), config, answer, args.review);
+        if args.format == Format::Text {
+            println!("{}", rubric.output_text(template, options));
+        }
+        let files: Vec<String> = matched.iter().map(|&i| names[i].clone()).collect();
+        read.extend(files.iter().cloned());
+        graded.push(SectionGrade::new(section, files, Some(rubric)));
+        texts.push(submission.contents);
+    }
+    match args.format {
+        Format::Text => println!("== total =="),
+        _ => report::note("== total =="),
+    }
+    Ok((
+        input::Submission {
+            name: read.join(" + "),
+            pdf_path: std::path::PathBuf::from("sections.pdf"),
+            contents: texts.join("\n\n"),
+        },
+        Some(Rubric::combine_sections(config, graded)),
+    ))
+}
+
+// How --include-config, --verbose and the rest render a report graded
+// under `config`
+fn report_options<'a>(args: &Args, config: &'a Config) -> ReportOptions<'a> {
+    ReportOptions {
+        config: args.include_config.then_some(config),
+        // a bare --verbose means every category
+        verbose: match &args.verbose {
+            Some(categories) if categories.is_empty() => vec![
+                LintCategory::Spelling,
+                LintCategory::Punctuation,
+                LintCategory::Capitalization,
+            ],
+            Some(categories) => categories.clone(),
+            None => Vec::new(),
+        },
+        max_errors_shown: args.max_errors_shown,
+        metadata: Metadata::new(config),
+        color: !args.no_color && args.color.enabled(),
+        explain_score: args.explain_score,
+        failures_only: args.failures_only,
+        order: config.report_order.clone(),
+    }
+}
+
+// The command line spelling of every value of a flag
+fn value_names<T: clap::ValueEnum>(keep: impl Fn(&T) -> bool) -> Vec<String> {
+    T::value_variants()
+        .iter()
+        .filter(|v| keep(v))
+        .filter_map(T::to_possible_value)
+        .map(|v| v.get_name().to_string())
+        .collect()
+}
+
+const SAMPLE_INPUT: &str = "\
+My video is about how plants make food. The leaves catch sunlight and turn water and air into sugar.
+
+https://youtu.be/abc123
+";
+
+// `quickgrade init`: scaffolding to start a rubric from, nothing is graded.
+// Nothing is written when any of the files is already there, unless `force`
+fn init(sample: bool, force: bool) {
+    let mut files = vec![(config::DEFAULT_CONFIG_FILE, config::CONFIG_TEMPLATE)];
+    if sample {
+        files.push((input::DEFAULT_INPUT, SAMPLE_INPUT));
+    }
+    if !force
+        && let Some((path, _)) = files
+            .iter()
+            .find(|(path, _)| std::path::Path::new(path).exists())
+    {
+        report::fatal(
+            "usage",
+            &format!("{} already exists, pass --force to overwrite it", path),
+        );
+    }
+    for (path, contents) in files {
+        if let Err(e) = std::fs::write(path, contents) {
+            report::fatal("config", &format!("can't write {}: {}", path, e));
+        }
+        report::note(&format!("wrote {}", path));
+    }
+}
+
+// What this build can do, for wrappers. Only ever add keys to it
+fn capabilities() -> serde_json::Value {
+    serde_json::json!({
+        "version": env!("CARGO_PKG_VERSION"),
+        "features": {
+            "clipboard": cfg!(feature = "clipboard"),
+            "network": cfg!(feature = "network"),
+            "pdf": cfg!(feature = "pdf"),
+            "server": cfg!(feature = "server"),
+            "sqlite": cfg!(feature = "sqlite"),
+        },
+        "formats": value_names(|f: &Format| *f != Format::Pdf || cfg!(feature = "pdf")),
+        "input_formats": value_names(|_: &input::InputFormat| true),
+    })
+}
+
+/// The command line tool, the binary's `main` is just this
+pub fn run() {
+    // logs go to stderr, RUST_LOG=debug shows every lint and RUST_LOG=trace where it is
+    env_logger::Builder::from_env(env_logger::Env::default().default_filter_or("warn")).init();
+    let args = Args::try_parse().unwrap_or_else(|e| {
+        // --help and --version come through here too and go to stdout as usual
+        if e.use_stderr() && std::env::args().any(|a| a == "--json-errors") {
+            report::use_json(true);
+            report::error("usage", e.to_string().trim());
+            std::process::exit(e.exit_code());
+        }
+        e.exit()
+    });
+    report::use_json(args.json_errors);
+    if let Some(format) = args.profile {
+        profile::enable(format);
+    }
+    if args.capabilities {
+        println!("{}", capabilities());
+        return;
+    }
+    if let Some(Command::Init { sample, force }) = args.command {
+        init(sample, force);
+        return;
+    }
+    let config = Config::load(&args).unwrap_or_else(|e| report::fatal("config", &e));
+    if args.show_rubric {
+        match args.format {
+            Format::Text | Format::Pdf => print!("{}", config.show_rubric()),
+            Format::Json | Format::Jsonl | Format::Csv => {
+                println!("{}", config.show_rubric_json())
+            }
+        }
+        return;
+    }
+    if args.format == Format::Pdf && !cfg!(feature = "pdf") {
+        report::fatal(
+            "unsupported",
+            "quickgrade was built without pdf support, rebuild with --features pdf",
+        );
+    }
+    if args.db.is_some() && !cfg!(feature = "sqlite") {
+        report::fatal(
+            "unsupported",
+            "quickgrade was built without sqlite support, rebuild with --features sqlite",
+        );
+    }
+    if args.config.len() > 1
+        && (args.diff_score.is_some()
+            || args.baseline.is_some()
+            || args.db.is_some()
+            || args.emit_sidecar
+            || args.review
+            || args.watch
+            || args.format == Format::Pdf)
+    {
+        report::fatal(
+            "usage",
+            "--diff-score, --baseline, --db, --emit-sidecar, --review, --watch and --format pdf take a single --config",
+        );
+    }
+    let template = args.template.as_ref().map(|path| {
+        let text = std::fs::read_to_string(path).unwrap_or_else(|e| {
+            report::fatal(
+                "template",
+                &format!("can't read template {}: {}", path.display(), e),
+            )
+        });
+        if let Err(e) = template::validate(&text) {
+            report::fatal(
+                "template",
+                &format!("bad template {}: {}", path.display(), e),
+            );
+        }
+        text
+    });
+    let options = report_options(&args, &config);
+    if let Some(Command::Serve { port, host }) = &args.command {
+        if !cfg!(feature = "server") {
+            report::fatal(
+                "unsupported",
+                "quickgrade was built without the server, rebuild with --features server",
+            );
+        }
+        serve(host, *port, &config, &options);
+        return;
+    }
+    let baseline = args.baseline.as_ref().map(|path| {
+        baseline::Baseline::load(path).unwrap_or_else(|e| report::fatal("baseline", &e))
+    });
+    let batch = input::is_batch(&args);
+    if batch && args.changed_only.is_some() {
+        report::fatal(
+            "usage",
+            "--changed-only takes a single submission, the next draft of the one given",
+        );
+    }
+    let sources = input::sources(&args).unwrap_or_else(|e| report::fatal("input", &e));
+    // --aggregate: one submission made of them all, answered and reported once
+    let grouped: Vec<String> = match args.aggregate {
+        true if sources.len() < 2 => report::fatal("usage", "--aggregate needs at least two files"),
+        true => sources.iter().map(|s| s.name()).collect(),
+        false => Vec::new(),
+    };
+    let sources = match args.aggregate {
+        true => vec![input::Source::Aggregate(sources)],
+        false => sources,
+    };
+    if !config.sections.is_empty()
+        && (args.aggregate || args.watch || args.config.len() > 1 || args.diff_score.is_some())
+    {
+        report::fatal(
+            "usage",
+            "sections grade the files named as one submission, without --aggregate, --watch, --diff-score or a second --config",
+        );
+    }
+    // the sections' files make one submission
+    let batch = batch && config.sections.is_empty();
+    let watched = args.watch.then(|| match sources.as_slice() {
+        [input::Source::File(path)] if input::is_fifo(path) => path.clone(),
+        _ => report::fatal(
+            "usage",
+            "--watch takes a single named pipe (made with mkfifo, Unix only)",
+        ),
+    });
+    let answers = args.seed_answers.as_ref().map(|path| {
+        let answers = answers::Answers::load(path).unwrap_or_else(|e| report::fatal("answers", &e));
+        let names: Vec<String> = sources.iter().map(|s| s.name()).collect();
+        for warning in answers.mismatches(&names, args.default_answer) {
+            report::warning("answers", &warning);
+        }
+        answers
+    });
+    let answer_for = |name: &str| {
+        answers
+            .as_ref()
+            .and_then(|a| a.lookup(name))
+            .or(args.default_answer.map(|a| a == Answer::Yes))
+            .map(Grade::new)
+    };
+    let comments = args.comments.as_ref().map(|path| {
+        let comments =
+            comments::Comments::load(path).unwrap_or_else(|e| report::fatal("comments", &e));
+        let names: Vec<String> = sources.iter().map(|s| s.name()).collect();
+        for warning in comments.unused(&names) {
+            report::warning("comments", &warning);
+        }
+        comments
+    });
+    let comment_for = |name: &str| {
+        comments
+            .as_ref()
+            .and_then(|c| c.lookup(name))
+            .map(String::from)
+            .or_else(|| args.ask_comment.then(Rubric::ask_comment).flatten())
+    };
+    if args.dry_run {
+        for path in args.config.iter().skip(1).chain(&args.diff_score) {
+            Config::load_from(&args, Some(path)).unwrap_or_else(|e| report::fatal("config", &e));
+        }
+        if dry_run(&sources, &args) {
+            std::process::exit(1);
+        }
+        println!("all checks passed: {} inputs", sources.len());
+        return;
+    }
+    if args.lint_only {
+        if matches!(args.format, Format::Csv | Format::Pdf) {
+            report::fatal("usage", "--lint-only prints text, json or jsonl");
+        }
+        let failed = lint_only(sources, &args, &config);
+        finish(failed);
+    }
+    if args.config.len() > 1 {
+        let rubrics: Vec<(String, Config)> = args
+            .config
+            .iter()
+            .map(|path| {
+                let config = Config::load_from(&args, Some(path))
+                    .unwrap_or_else(|e| report::fatal("config", &e));
+                (path.display().to_string(), config)
+            })
+            .collect();
+        let failed = grade_rubrics(
+            sources,
+            &args,
+            &rubrics,
+            template.as_deref(),
+            answer_for,
+            comment_for,
+        );
+        finish(failed);
+    }
+    if let Some(path) = &args.diff_score {
+        let other =
+            Config::load_from(&args, Some(path)).unwrap_or_else(|e| report::fatal("config", &e));
+        let (rows, failed) = diff_scores(
+            sources,
+            input::Reading::of(&args),
+            &config,
+            &other,
+            answer_for,
+        );
+        match args.format {
+            Format::Csv => print!("{}", diff::csv(&rows)),
+            Format::Json | Format::Jsonl => println!(
+                "{}",
+                serde_json::to_string_pretty(&rows).expect("failed to serialize scores")
+            ),
+            Format::Text | Format::Pdf => print!("{}", diff::table(&rows)),
+        }
+        finish(failed);
+    }
+    let mut failed = false;
+    // --format json in batch mode prints one array at the end
+    let mut graded: Vec<(String, Rubric)> = Vec::new();
+    let mut scores: Vec<(String, f32)> = Vec::new();
+    // --db writes them all at the end in one transaction
+    let mut stored: Vec<(String, Rubric)> = Vec::new();
+    // prompting for the questions or under --review keeps it to one file at
+    // a time, the grader answers them in order
+    let interactive = args.review
+        || (Rubric::needs_answer(&config)
+            && sources.iter().any(|s| answer_for(&s.name()).is_none()));
+    let jobs = args.jobs.map_or_else(
+        || std::thread::available_parallelism().map_or(1, |n| n.get()),
+        |n| n.get(),
+    );
+    let reading = input::Reading::of(&args);
+    // --watch opens the pipe again for every draft, until reading it fails
+    let sources: Box<dyn Iterator<Item = Submitted>> = match watched {
+        Some(path) => Box::new(std::iter::repeat_with(move || {
+            input::Source::File(path.clone())
+                .read(reading)
+                .map(|submission| (submission, None))
+        })),
+        None if !config.sections.is_empty() => Box::new(std::iter::once(grade_sections_of(
+            sources,
+            &args,
+            &config,
+            template.as_deref(),
+            &options,
+            answer_for,
+        ))),
+        None if jobs > 1 && sources.len() > 1 && !interactive => {
+            log::debug!("grading on {} threads", jobs.min(sources.len()));
+            let answered = sources
+                .into_iter()
+                .map(|s| {
+                    let answer = answer_for(&s.name());
+                    (s, answer)
+                })
+                .collect::<Vec<_>>();
+            let jobs = jobs.min(answered.len());
+            Box::new(grade_in_parallel(answered, jobs, reading, &config))
+        }
+        None => Box::new(
+            sources
+                .into_iter()
+                .map(|source| source.read(reading).map(|submission| (submission, None))),
+        ),
+    };
+    for read in sources {
+        let (submission, pregraded) = match read {
+            Ok(read) => read,
+            Err(e) if args.watch => report::fatal("input", &e),
+            Err(e) => {
+                report::error("input", &e);
+                failed = true;
+                continue;
+            }
+        };
+        // a writer that closed the pipe without writing anything
+        if args.watch && submission.contents.trim().is_empty() {
+            log::debug!("nothing written to {}", submission.name);
+            continue;
+        }
+        if batch {
+            match args.format {
+                Format::Text => println!("== {} ==", submission.name),
+                _ => report::note(&format!("== {} ==", submission.name)),
+            }
+        }
+        let aggregated = args.aggregate.then(|| Aggregated {
+            files: grouped.clone(),
+            words: config.count_words(WordCount::of_text(&submission.contents)),
+        });
+        let mut rubric = pregraded.unwrap_or_else(|| {
+            let answer = answer_for(&submission.name);
+            Rubric::from_string(submission.contents, &config, answer, args.review)
+        });
+        rubric.comment = comment_for(&submission.name);
+        rubric.aggregated = aggregated;
+        scores.push((submission.name.clone(), rubric.score_percent()));
+        if args.db.is_some() {
+            stored.push((submission.name.clone(), rubric.clone()));
+        }
+        if args.emit_sidecar {
+            // where the pdf would go
+            let path = submission.pdf_path.with_extension(sidecar::EXTENSION);
+            match sidecar::write(&rubric, &options, &config, &submission.name, &path) {
+                Ok(()) => report::note(&format!("wrote {}", path.display())),
+                Err(e) => {
+                    report::error("sidecar", &e);
+                    failed = true;
+                }
+            }
+        }
+        match args.format {
+            Format::Text => println!("{}", rubric.output_text(template.as_deref(), &options)),
+            Format::Json if batch => graded.push((submission.name, rubric)),
+            Format::Json => println!("{}", rubric.output_json(&options)),
+            Format::Jsonl => println!("{}", rubric.output_jsonl(&options, &submission.name)),
+            Format::Csv => {
+                if scores.len() == 1 {
+                    println!("{}", rubric.csv_header(&options));
+                }
+                println!("{}", rubric.csv_row(&options, &submission.name));
+            }
+            Format::Pdf => write_pdf(&rubric, &options, &submission.pdf_path),
+        }
+    }
+    if args.format == Format::Json && batch {
+        let reports: Vec<JsonReport> = graded
+            .iter()
+            .map(|(name, rubric)| rubric.json_report(&options, Some(name)))
+            .collect();
+        println!(
+            "{}",
+            serde_json::to_string_pretty(&reports).expect("failed to serialize rubric")
+        );
+    }
+    if let Some(path) = &args.db
+        && let Err(e) = store_grades(
+            path,
+            config.assignment.as_deref().unwrap_or_default(),
+            &stored,
+        )
+    {
+        report::error("db", &e);
+        failed = true;
+    }
+    if let Some(baseline) = &baseline {
+        let comparison = baseline.compare(&scores, args.tolerance);
+        // keep machine readable output clean
+        match args.format {
+            Format::Text => print!("{}", comparison.table),
+            _ => report::note(comparison.table.trim_end()),
+        }
+        if comparison.over_tolerance > 0 {
+            report::error(
+                "baseline",
+                &format!(
+                    "{} scores differ from the baseline by more than {}",
+                    comparison.over_tolerance, args.tolerance
+                ),
+            );
+            failed = true;
+        }
+    }
+    finish(failed);
+}
+
+// The end of a grading run, after the --profile breakdown
+fn finish(failed: bool) -> ! {
+    profile::dump();
+    std::process::exit(if failed { 1 } else { 0 });
+}
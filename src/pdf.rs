@@ -0,0 +1,111 @@
+//! --format pdf: a printable feedback sheet with the score, the criteria and every mistake
+use crate::{Metadata, Rubric};
+use printpdf::{
+    BuiltinFont, IndirectFontRef, Mm, PdfDocument, PdfDocumentReference, PdfLayerReference,
+};
+use std::fs::File;
+use std::io::BufWriter;
+use std::path::Path;
+
+const PAGE_WIDTH: f32 = 210.0;
+const PAGE_HEIGHT: f32 = 297.0;
+const MARGIN: f32 = 20.0;
+const LINE: f32 = 6.0;
+
+// Writes lines top to bottom and starts a new page when it runs out of room
+struct Sheet {
+    doc: PdfDocumentReference,
+    layer: PdfLayerReference,
+    font: IndirectFontRef,
+    bold: IndirectFontRef,
+    y: f32,
+}
+impl Sheet {
+    fn new(title: &str) -> Result<Sheet, String> {
+        let (doc, page, layer) =
+            PdfDocument::new(title, Mm(PAGE_WIDTH), Mm(PAGE_HEIGHT), "Feedback");
+        let font = doc
+            .add_builtin_font(BuiltinFont::Helvetica)
+            .map_err(|e| e.to_string())?;
+        let bold = doc
+            .add_builtin_font(BuiltinFont::HelveticaBold)
+            .map_err(|e| e.to_string())?;
+        let layer = doc.get_page(page).get_layer(layer);
+        Ok(Sheet {
+            doc,
+            layer,
+            font,
+            bold,
+            y: PAGE_HEIGHT - MARGIN,
+        })
+    }
+    fn line(&mut self, text: &str, size: f32, bold: bool) {
+        if self.y < MARGIN {
+            let (page, layer) = self
+                .doc
+                .add_page(Mm(PAGE_WIDTH), Mm(PAGE_HEIGHT), "Feedback");
+            self.layer = self.doc.get_page(page).get_layer(layer);
+            self.y = PAGE_HEIGHT - MARGIN;
+        }
+        let font = if bold { &self.bold } else { &self.font };
+        self.layer
+            .use_text(text, size, Mm(MARGIN), Mm(self.y), font);
+        self.y -= LINE * size / 11.0;
+    }
+}
+
+pub fn write(rubric: &Rubric, metadata: &Metadata, path: &Path) -> Result<(), String> {
+    let mut sheet = Sheet::new("Feedback")?;
+    for (label, value) in [
+        ("Assignment", &metadata.assignment),
+        ("Due", &metadata.due_date),
+        ("Grader", &metadata.grader),
+    ] {
+        if let Some(value) = value {
+            sheet.line(&format!("{}: {}", label, value), 11.0, false);
+        }
+    }
+    if !metadata.is_empty() {
+        sheet.line("", 11.0, false);
+    }
+    sheet.line(
+        &format!("Final score: {}%", rubric.score_percent()),
+        18.0,
+        true,
+    );
+    if let Some(comment) = &rubric.comment {
+        sheet.line(comment, 11.0, false);
+    }
+    sheet.line("", 11.0, false);
+    for c in &rubric.criteria {
+        let mut row = format!(
+            "{}% of {}%: {}",
+            rubric.points(c),
+            rubric.weight(c),
+            c.description
+        );
+        if c.warning {
+            row += " [warning only]";
+        }
+        if !c.detail.is_empty() {
+            row += &format!(" ({})", c.detail);
+        }
+        sheet.line(&row, 11.0, false);
+    }
+    for c in &rubric.criteria {
+        let errors: Vec<_> = rubric.errors_in(c).collect();
+        if errors.is_empty() {
+            continue;
+        }
+        sheet.line("", 11.0, false);
+        sheet.line(&format!("Mistakes under {}", c.name), 13.0, true);
+        for e in errors {
+            sheet.line(&format!("- '{}': {}", e.text, e.message), 11.0, false);
+        }
+    }
+    let file = File::create(path).map_err(|e| format!("can't create {}: {}", path.display(), e))?;
+    sheet
+        .doc
+        .save(&mut BufWriter::new(file))
+        .map_err(|e| format!("can't write {}: {}", path.display(), e))
+}
@@ -0,0 +1,113 @@
+//! --profile: where grading spends its time, each step summed over every file
+//! and printed on stderr at the end, as a tree or as folded stacks
+//! (`quickgrade;lint;rules;Spelling 1234`, self time in microseconds) for
+//! flamegraph tools. Nothing is timed without it
+use std::collections::BTreeMap;
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{Duration, Instant};
+
+#[derive(clap::ValueEnum, Clone, Copy, Debug, PartialEq)]
+pub enum ProfileFormat {
+    /// An indented tree of total times
+    Tree,
+    /// One `frame;frame;frame microseconds` line per step, self time only
+    Folded,
+}
+
+const ROOT: &str = "quickgrade";
+
+static ENABLED: AtomicBool = AtomicBool::new(false);
+// step path (frames separated by ;) -> total time and how many times it ran
+static STEPS: Mutex<BTreeMap<String, (Duration, usize)>> = Mutex::new(BTreeMap::new());
+static FORMAT: Mutex<Option<ProfileFormat>> = Mutex::new(None);
+
+pub fn enable(format: ProfileFormat) {
+    *FORMAT.lock().unwrap() = Some(format);
+    ENABLED.store(true, Ordering::Relaxed);
+}
+
+fn enabled() -> bool {
+    ENABLED.load(Ordering::Relaxed)
+}
+
+// Adds to the step's total, `path` is its frames below the root joined by ;
+pub fn record(path: &str, elapsed: Duration) {
+    if !enabled() {
+        return;
+    }
+    let mut steps = STEPS.lock().unwrap();
+    let step = steps.entry(path.to_string()).or_default();
+    step.0 += elapsed;
+    step.1 += 1;
+}
+
+// Runs f, timed as the step at `path` under --profile
+pub fn time<T>(path: &str, f: impl FnOnce() -> T) -> T {
+    if !enabled() {
+        return f();
+    }
+    let start = Instant::now();
+    let out = f();
+    record(path, start.elapsed());
+    out
+}
+
+fn parent(path: &str) -> Option<&str> {
+    path.rsplit_once(';').map(|(parent, _)| parent)
+}
+
+fn children<'a>(
+    steps: &'a BTreeMap<String, (Duration, usize)>,
+    of: Option<&str>,
+) -> Vec<(&'a String, &'a (Duration, usize))> {
+    let mut children: Vec<_> = steps.iter().filter(|(p, _)| parent(p) == of).collect();
+    // slowest first
+    children.sort_by_key(|(_, (total, _))| std::cmp::Reverse(*total));
+    children
+}
+
+fn tree(steps: &BTreeMap<String, (Duration, usize)>, of: Option<&str>, depth: usize) -> String {
+    let mut out = String::new();
+    for (path, (total, runs)) in children(steps, of) {
+        let name = path.rsplit(';').next().unwrap_or(path);
+        out += &format!(
+            "{:indent$}{:<width$} {:>10.2} ms  x{}\n",
+            "",
+            name,
+            total.as_secs_f64() * 1000.0,
+            runs,
+            indent = 2 * depth,
+            width = 34usize.saturating_sub(2 * depth)
+        );
+        out += &tree(steps, Some(path), depth + 1);
+    }
+    out
+}
+
+fn folded(steps: &BTreeMap<String, (Duration, usize)>) -> String {
+    let mut out = String::new();
+    for (path, (total, _)) in steps {
+        let nested: Duration = children(steps, Some(path)).iter().map(|(_, s)| s.0).sum();
+        let own = total.saturating_sub(nested).as_micros();
+        if own > 0 {
+            out += &format!("{};{} {}\n", ROOT, path, own);
+        }
+    }
+    out
+}
+
+// Prints what was timed, at the end of a run
+pub fn dump() {
+    let Some(format) = *FORMAT.lock().unwrap() else {
+        return;
+    };
+    let steps = STEPS.lock().unwrap();
+    match format {
+        ProfileFormat::Tree => eprint!(
+            "profile (linting runs alongside the questions prompt and other files):\n{}",
+            tree(&steps, None, 1)
+        ),
+        ProfileFormat::Folded => eprint!("{}", folded(&steps)),
+    }
+}
@@ -0,0 +1,98 @@
+//! Leaves out the assignment prompt students paste above their answer, found
+//! by `prompt_marker` or by matching the text of `prompt_file`
+use crate::Config;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+
+// Share of a paragraph's words that have to appear in the prompt for it to
+// count as pasted, some slack for a fixed typo or a dropped line
+const PROMPT_OVERLAP: f32 = 0.8;
+// Shorter paragraphs (a title, a name) match too easily to cut on their own
+const MIN_PROMPT_WORDS: usize = 4;
+
+/// What was left out from the top of a submission
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PromptStripped {
+    pub lines: usize,
+    pub words: usize,
+}
+impl PromptStripped {
+    pub fn describe(&self) -> String {
+        format!(
+            "Left out the assignment prompt: {} lines, {} words",
+            self.lines, self.words
+        )
+    }
+}
+
+fn words(text: &str) -> Vec<String> {
+    text.split(|c: char| !c.is_alphanumeric() && c != '\'')
+        .filter(|w| !w.is_empty())
+        .map(str::to_lowercase)
+        .collect()
+}
+
+// Byte offset just past the first line matching the marker
+fn marker_end(contents: &str, marker: &str) -> Option<usize> {
+    // checked when the config was loaded
+    let regex = Regex::new(marker).unwrap();
+    let mut end = 0;
+    for line in contents.split_inclusive('\n') {
+        end += line.len();
+        if regex.is_match(line.trim()) {
+            return Some(end);
+        }
+    }
+    None
+}
+
+// Byte offset just past the last of the paragraphs at the top whose words are
+// mostly the prompt's
+fn prompt_end(contents: &str, prompt: &str) -> Option<usize> {
+    let known: HashSet<String> = words(prompt).into_iter().collect();
+    let mut end = None;
+    let mut start = 0;
+    let mut paragraph = String::new();
+    for line in contents.split_inclusive('\n') {
+        start += line.len();
+        if !line.trim().is_empty() {
+            paragraph += line;
+            continue;
+        }
+        if paragraph.is_empty() {
+            continue;
+        }
+        let words = words(&paragraph);
+        let shared = words.iter().filter(|w| known.contains(*w)).count();
+        if (shared as f32) < words.len() as f32 * PROMPT_OVERLAP {
+            return end;
+        }
+        if words.len() >= MIN_PROMPT_WORDS {
+            end = Some(start);
+        }
+        paragraph.clear();
+    }
+    // the last paragraph is never checked, whatever is left is the answer
+    end
+}
+
+// The contents with the prompt's lines emptied, so line numbers stay the same
+pub(crate) fn strip(contents: String, config: &Config) -> (String, Option<PromptStripped>) {
+    let end = crate::profile::time("prompt", || match (&config.prompt_marker, &config.prompt) {
+        (Some(marker), _) => marker_end(&contents, marker),
+        (None, Some(prompt)) => prompt_end(&contents, prompt),
+        (None, None) => None,
+    });
+    let Some(end) = end else {
+        return (contents, None);
+    };
+    let (prompt, answer) = contents.split_at(end);
+    let stripped = PromptStripped {
+        lines: prompt.lines().filter(|l| !l.trim().is_empty()).count(),
+        words: prompt.split_whitespace().count(),
+    };
+    log::debug!("{}", stripped.describe());
+    let blanked = "\n".repeat(prompt.matches('\n').count()) + answer;
+    (blanked, Some(stripped))
+}
@@ -0,0 +1,105 @@
+//! sections: an assignment made of several required files, e.g. intro.txt,
+//! body.txt and conclusion.txt, each graded on its own and weighted into one
+//! total. Unlike --aggregate the files aren't read as one text
+use crate::config::{Config, Section};
+use crate::{Criterion, Grade, Rubric};
+use serde::{Deserialize, Serialize};
+
+// A section's own rubric, None when no file matched it
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub(crate) struct SectionGrade {
+    name: String,
+    files: Vec<String>,
+    weight: f32,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    rubric: Option<Rubric>,
+}
+
+// For each section the indexes of the files whose name matches its glob, then
+// the files matching none. A file goes to the first section it matches
+pub(crate) fn assign(sections: &[Section], names: &[String]) -> (Vec<Vec<usize>>, Vec<usize>) {
+    let patterns: Vec<glob::Pattern> = sections
+        .iter()
+        // checked when the config was loaded
+        .map(|s| glob::Pattern::new(&s.files).expect("bad section pattern"))
+        .collect();
+    let mut assigned = vec![Vec::new(); sections.len()];
+    let mut unmatched = Vec::new();
+    for (i, name) in names.iter().enumerate() {
+        let file_name = std::path::Path::new(name)
+            .file_name()
+            .and_then(|n| n.to_str())
+            .unwrap_or(name);
+        match patterns.iter().position(|p| p.matches(file_name)) {
+            Some(section) => assigned[section].push(i),
+            None => unmatched.push(i),
+        }
+    }
+    (assigned, unmatched)
+}
+
+impl SectionGrade {
+    pub(crate) fn new(
+        section: &Section,
+        files: Vec<String>,
+        rubric: Option<Rubric>,
+    ) -> SectionGrade {
+        SectionGrade {
+            name: section.name.clone(),
+            files,
+            weight: section.weight,
+            rubric,
+        }
+    }
+    // What a section's criterion says about it in the combined rubric
+    fn detail(&self, pattern: &str) -> String {
+        let Some(rubric) = &self.rubric else {
+            return format!("no file matches {}", pattern);
+        };
+        let short: Vec<&str> = rubric
+            .criteria
+            .iter()
+            .filter(|c| rubric.needs_work(c))
+            .map(|c| c.name.as_str())
+            .collect();
+        match short.as_slice() {
+            [] => format!("{}%", rubric.score_percent()),
+            _ => format!("{}%, short on {}", rubric.score_percent(), short.join(", ")),
+        }
+    }
+}
+
+impl Rubric {
+    // One criterion per section, worth its weight and graded with the
+    // section's own score. A missing section gets nothing
+    pub(crate) fn combine_sections(config: &Config, sections: Vec<SectionGrade>) -> Rubric {
+        let criteria = config
+            .sections
+            .iter()
+            .zip(&sections)
+            .map(|(section, graded)| Criterion {
+                name: graded.name.clone(),
+                description: match graded.files.as_slice() {
+                    [] => format!("Section {}", graded.name),
+                    files => format!("Section {} ({})", graded.name, files.join(", ")),
+                },
+                grade: match &graded.rubric {
+                    Some(rubric) => Grade {
+                        val: Some(rubric.score_fraction()),
+                    },
+                    None => Grade::new(false),
+                },
+                weight: graded.weight,
+                detail: graded.detail(&section.files),
+                warning: false,
+            })
+            .collect();
+        Rubric {
+            criteria,
+            unevaluated: config.unevaluated,
+            rounding: config.rounding,
+            sections,
+            ..Default::default()
+        }
+    }
+}
@@ -0,0 +1,114 @@
+//! quickgrade serve: grades the text POSTed to /grade and answers with the
+//! JSON report, see the README for the request and response
+use crate::{Config, ReportOptions, Rubric, grade_text, input, report};
+use harper_core::spell::{Dictionary, FstDictionary};
+use serde::Deserialize;
+use std::io::Read;
+use tiny_http::{Header, Method, Request, Response, Server};
+
+// Requests handled at once, each worker lints on its own thread
+const WORKERS: usize = 4;
+// Bigger bodies get a 413, no essay comes close
+const MAX_BODY: usize = 1024 * 1024;
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct GradeRequest {
+    text: String,
+    /// The questions answer, estimated under auto_questions or left ungraded when missing
+    #[serde(default)]
+    questions_answered: Option<bool>,
+}
+
+// Serves until the process is killed. Every worker shares the config and
+// harper's curated dictionary, an Arc loaded once up front instead of on the
+// first request. There's no authentication, so host is 127.0.0.1 unless
+// --host says otherwise
+pub fn serve(
+    host: &str,
+    port: u16,
+    config: &Config,
+    options: &ReportOptions,
+) -> Result<(), String> {
+    let server = Server::http((host, port))
+        .map_err(|e| format!("can't listen on {}:{}: {}", host, port, e))?;
+    let dictionary = FstDictionary::curated();
+    log::debug!("dictionary loaded, {} words", dictionary.word_count());
+    report::note(&format!("listening on http://{}:{}", host, port));
+    std::thread::scope(|scope| {
+        for _ in 0..WORKERS {
+            scope.spawn(|| {
+                for request in server.incoming_requests() {
+                    respond(request, config, options);
+                }
+            });
+        }
+    });
+    Ok(())
+}
+
+fn respond(mut request: Request, config: &Config, options: &ReportOptions) {
+    let path = request
+        .url()
+        .split('?')
+        .next()
+        .unwrap_or_default()
+        .to_string();
+    log::debug!("{} {}", request.method(), path);
+    let (status, body) = match (request.method(), path.as_str()) {
+        (Method::Post, "/grade") => match grade(&mut request, config, options) {
+            Ok(body) => (200, body),
+            Err((status, e)) => (status, error(&e)),
+        },
+        (_, "/grade") => (405, error("/grade only takes POST")),
+        _ => (
+            404,
+            error(&format!("no such endpoint {}, try POST /grade", path)),
+        ),
+    };
+    let header = Header::from_bytes("Content-Type", "application/json").expect("valid header");
+    let response = Response::from_string(body)
+        .with_status_code(status)
+        .with_header(header);
+    if let Err(e) = request.respond(response) {
+        report::warning("server", &format!("can't send the response: {}", e));
+    }
+}
+
+fn error(message: &str) -> String {
+    serde_json::json!({ "error": message }).to_string()
+}
+
+// The report, or the status to answer with and why
+fn grade(
+    request: &mut Request,
+    config: &Config,
+    options: &ReportOptions,
+) -> Result<String, (u16, String)> {
+    if request
+        .body_length()
+        .is_some_and(|length| length > MAX_BODY)
+    {
+        return Err((413, format!("request over {} bytes", MAX_BODY)));
+    }
+    let mut body = Vec::new();
+    request
+        .as_reader()
+        .take(MAX_BODY as u64 + 1)
+        .read_to_end(&mut body)
+        .map_err(|e| (400, format!("can't read the request: {}", e)))?;
+    if body.len() > MAX_BODY {
+        return Err((413, format!("request over {} bytes", MAX_BODY)));
+    }
+    let graded: GradeRequest =
+        serde_json::from_slice(&body).map_err(|e| (400, format!("bad request: {}", e)))?;
+    // the same cleanup a file gets
+    let (text, _) = input::read_text(graded.text.as_bytes(), None)
+        .map_err(|e| (400, format!("bad text: {}", e)))?;
+    let rubric = match graded.questions_answered {
+        Some(answer) => Rubric::from_string_with_answers(&text, config, answer),
+        None => grade_text(&text, config),
+    };
+    Ok(serde_json::to_string(&rubric.json_report(options, None))
+        .expect("failed to serialize rubric"))
+}
@@ -0,0 +1,70 @@
+//! --emit-sidecar: the whole result of grading a file as JSON next to it,
+//! `essay.txt` gets `essay.quickgrade.json`, to audit or load back later
+use crate::{Config, JsonReport, ReportOptions, Rubric, SCHEMA_VERSION};
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use std::time::SystemTime;
+
+pub const EXTENSION: &str = "quickgrade.json";
+
+// The JSON report with everything it can carry
+#[derive(Serialize)]
+struct Written<'a> {
+    #[serde(flatten)]
+    report: JsonReport<'a>,
+    graded_at: String,
+}
+
+/// A sidecar read back: the graded rubric, the config it was graded under
+/// and when
+#[derive(Debug, Clone, Deserialize)]
+pub struct Sidecar {
+    pub schema_version: u32,
+    /// The path or url as it was given
+    pub file: String,
+    /// UTC, RFC 3339
+    pub graded_at: String,
+    pub config: Config,
+    pub config_hash: String,
+    #[serde(flatten)]
+    pub rubric: Rubric,
+}
+
+impl Sidecar {
+    /// Reads a `.quickgrade.json` file, only ones this version's JSON report
+    /// schema wrote
+    pub fn read(path: &Path) -> Result<Sidecar, String> {
+        let text = std::fs::read_to_string(path)
+            .map_err(|e| format!("can't read {}: {}", path.display(), e))?;
+        let sidecar: Sidecar =
+            serde_json::from_str(&text).map_err(|e| format!("{}: {}", path.display(), e))?;
+        if sidecar.schema_version != SCHEMA_VERSION {
+            return Err(format!(
+                "{}: schema_version {}, this version reads {}",
+                path.display(),
+                sidecar.schema_version,
+                SCHEMA_VERSION
+            ));
+        }
+        Ok(sidecar)
+    }
+}
+
+pub(crate) fn write(
+    rubric: &Rubric,
+    options: &ReportOptions,
+    config: &Config,
+    file: &str,
+    path: &Path,
+) -> Result<(), String> {
+    let written = Written {
+        report: JsonReport {
+            config: Some(config),
+            config_hash: Some(config.hash()),
+            ..rubric.json_report(options, Some(file))
+        },
+        graded_at: humantime::format_rfc3339_seconds(SystemTime::now()).to_string(),
+    };
+    let json = serde_json::to_string_pretty(&written).expect("failed to serialize rubric");
+    std::fs::write(path, json + "\n").map_err(|e| format!("can't write {}: {}", path.display(), e))
+}
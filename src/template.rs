@@ -0,0 +1,277 @@
+//! Renders the text report through a tinytemplate, templates/default.tmpl is the stock one
+use crate::{Config, Criterion, Grade, LintCategory, LintError, Metadata, ReportOptions, Rubric};
+use harper_core::linting::LintKind;
+use serde::Serialize;
+use tinytemplate::TinyTemplate;
+use tinytemplate::error::Error;
+
+pub const DEFAULT_TEMPLATE: &str = include_str!("../templates/default.tmpl");
+
+// The only place colors are decided, set ReportOptions.color to turn them off
+const GREEN: &str = "\x1b[32m";
+const YELLOW: &str = "\x1b[33m";
+const RED: &str = "\x1b[31m";
+const RESET: &str = "\x1b[0m";
+
+// Everything a template can reference. Scores are preformatted the same way
+// the plain report prints them so `{score}` renders as `80` and not `80.0`
+#[derive(Serialize)]
+struct Context {
+    score: String,
+    spelling_lints: Vec<ErrorContext>,
+    punctuation_lints: Vec<ErrorContext>,
+    capitalization_lints: Vec<ErrorContext>,
+    /// Every criterion in report order
+    criteria: Vec<CriterionContext>,
+    /// Empty unless --include-config
+    config: String,
+    /// Mistakes in the --verbose categories, empty without it
+    details: Vec<ErrorContext>,
+    /// "...and 12 more spelling mistakes" for each category cut short by --max-errors-shown
+    omitted: Vec<String>,
+    /// Ends a colored row, empty when not colored
+    reset: String,
+    /// Each empty unless given
+    assignment: String,
+    due_date: String,
+    grader: String,
+    /// The grader's comment, empty when there is none
+    comment: String,
+    /// How much of an assignment prompt was left out, empty when none was
+    prompt_stripped: String,
+    /// The files graded together and their words under --aggregate, empty otherwise
+    aggregated: String,
+    /// Misspelled tokens spelling_ignore kept from counting, comma separated
+    ignored_tokens: String,
+    /// British spellings british_spellings accepted, comma separated
+    british_accepted: String,
+}
+
+#[derive(Serialize)]
+struct CriterionContext {
+    name: String,
+    description: String,
+    score: String,
+    /// Share of the final score it is worth
+    weight: String,
+    detail: String,
+    /// Reported but not scored
+    warning: bool,
+    /// ANSI escape for the row, green when passed, yellow partial, red failed,
+    /// empty when not colored or not evaluated
+    color: String,
+    /// Hint about likely proper nouns when it failed on spelling, only with --verbose
+    note: String,
+}
+
+#[derive(Serialize)]
+struct ErrorContext {
+    category: Option<LintCategory>,
+    criterion: String,
+    text: String,
+    message: String,
+    /// How many mistakes it counts as, empty unless early_error_weight is set
+    weight: String,
+}
+
+impl From<&LintError> for ErrorContext {
+    fn from(e: &LintError) -> ErrorContext {
+        ErrorContext {
+            category: e.category,
+            criterion: e.criterion.clone(),
+            text: e.text.clone(),
+            message: e.message.clone(),
+            weight: e.weight.map(|w| format!("{:.1}", w)).unwrap_or_default(),
+        }
+    }
+}
+
+fn lints_in(rubric: &Rubric, category: LintCategory) -> Vec<ErrorContext> {
+    rubric
+        .errors
+        .iter()
+        .filter(|e| e.category == Some(category))
+        .map(ErrorContext::from)
+        .collect()
+}
+
+fn criterion_context(rubric: &Rubric, c: &Criterion, options: &ReportOptions) -> CriterionContext {
+    CriterionContext {
+        name: c.name.clone(),
+        description: c.description.clone(),
+        score: rubric.points(c).to_string(),
+        weight: rubric.weight(c).to_string(),
+        detail: c.detail.clone(),
+        warning: c.warning,
+        color: match c.grade.val {
+            _ if !options.color => "",
+            None => "",
+            Some(v) if v >= 1.0 => GREEN,
+            Some(v) if v > 0.0 => YELLOW,
+            Some(_) => RED,
+        }
+        .to_string(),
+        note: match rubric.capitalized_misspellings(c) {
+            n if n > 0 && !options.verbose.is_empty() && !c.grade.get() => format!(
+                "{} of {} misspellings are capitalized words, likely names; a custom dictionary might help",
+                n,
+                rubric
+                    .errors_in(c)
+                    .filter(|e| e.category == Some(LintCategory::Spelling))
+                    .count()
+            ),
+            _ => String::new(),
+        },
+    }
+}
+
+// The --verbose mistakes, at most max_errors_shown of each category, and a
+// line for each category that had more
+fn details(rubric: &Rubric, options: &ReportOptions) -> (Vec<ErrorContext>, Vec<String>) {
+    let mut shown: Vec<(LintCategory, usize)> = Vec::new();
+    let mut details = Vec::new();
+    for e in &rubric.errors {
+        let Some(category) = e.category.filter(|c| options.verbose.contains(c)) else {
+            continue;
+        };
+        let count = match shown.iter_mut().find(|(c, _)| *c == category) {
+            Some((_, count)) => count,
+            None => {
+                shown.push((category, 0));
+                &mut shown.last_mut().unwrap().1
+            }
+        };
+        *count += 1;
+        if options.max_errors_shown == 0 || *count <= options.max_errors_shown {
+            details.push(ErrorContext::from(e));
+        }
+    }
+    let omitted = shown
+        .into_iter()
+        .filter(|(_, count)| options.max_errors_shown > 0 && *count > options.max_errors_shown)
+        .map(|(category, count)| {
+            format!(
+                "...and {} more {} mistakes",
+                count - options.max_errors_shown,
+                format!("{:?}", category).to_lowercase()
+            )
+        })
+        .collect();
+    (details, omitted)
+}
+
+// The criteria named in `order` in that order, names the rubric doesn't have
+// (unknown or turned off) are skipped. All of them when it's empty
+fn ordered<'a>(rubric: &'a Rubric, order: &[String]) -> Vec<&'a Criterion> {
+    if order.is_empty() {
+        return rubric.criteria.iter().collect();
+    }
+    order
+        .iter()
+        .filter_map(|name| {
+            let found = rubric.criteria.iter().find(|c| c.name == *name);
+            if found.is_none() {
+                log::debug!("report_order: no {} criterion, skipped", name);
+            }
+            found
+        })
+        .collect()
+}
+
+fn context(rubric: &Rubric, options: &ReportOptions) -> Context {
+    let (details, omitted) = details(rubric, options);
+    Context {
+        score: rubric.score_percent().to_string(),
+        spelling_lints: lints_in(rubric, LintCategory::Spelling),
+        punctuation_lints: lints_in(rubric, LintCategory::Punctuation),
+        capitalization_lints: lints_in(rubric, LintCategory::Capitalization),
+        criteria: ordered(rubric, &options.order)
+            .into_iter()
+            .filter(|c| !options.failures_only || rubric.needs_work(c))
+            .map(|c| criterion_context(rubric, c, options))
+            .collect(),
+        config: options
+            .config
+            .map(|c| format!("{} (hash {})", c.summary(), c.hash()))
+            .unwrap_or_default(),
+        ignored_tokens: rubric.ignored_tokens.join(", "),
+        british_accepted: rubric.british_accepted.join(", "),
+        details,
+        omitted,
+        reset: if options.color { RESET } else { "" }.to_string(),
+        assignment: options.metadata.assignment.clone().unwrap_or_default(),
+        due_date: options.metadata.due_date.clone().unwrap_or_default(),
+        grader: options.metadata.grader.clone().unwrap_or_default(),
+        comment: rubric.comment.clone().unwrap_or_default(),
+        prompt_stripped: rubric
+            .prompt_stripped
+            .as_ref()
+            .map(|p| p.describe())
+            .unwrap_or_default(),
+        aggregated: rubric
+            .aggregated
+            .as_ref()
+            .map(|a| a.describe())
+            .unwrap_or_default(),
+    }
+}
+
+pub fn render(template: &str, rubric: &Rubric, options: &ReportOptions) -> Result<String, Error> {
+    let mut tt = TinyTemplate::new();
+    tt.set_default_formatter(&tinytemplate::format_unescaped);
+    tt.add_template("report", template)?;
+    tt.render("report", &context(rubric, options))
+}
+
+// tinytemplate only notices an unknown variable while rendering it, so render
+// once against a rubric that has something in every list to reach loop bodies
+pub fn validate(template: &str) -> Result<(), Error> {
+    let mut sample = Rubric::default();
+    sample.criteria.push(Criterion {
+        name: String::new(),
+        description: String::new(),
+        grade: Grade::empty(),
+        weight: 1.0,
+        detail: String::new(),
+        warning: false,
+    });
+    for category in [
+        LintCategory::Spelling,
+        LintCategory::Spelling,
+        LintCategory::Punctuation,
+        LintCategory::Capitalization,
+    ] {
+        sample.errors.push(LintError {
+            category: Some(category),
+            kind: LintKind::default(),
+            criterion: String::new(),
+            text: String::new(),
+            message: String::new(),
+            list_item: false,
+            position: 0.0,
+            weight: None,
+            british: false,
+            line: 1,
+            column: 1,
+            suggestions: Vec::new(),
+        });
+    }
+    let config = Config::default();
+    let options = ReportOptions {
+        config: Some(&config),
+        verbose: vec![LintCategory::Spelling],
+        // below the sample's spelling mistakes so omitted isn't empty either
+        max_errors_shown: 1,
+        // non-empty so the template's ifs on them get rendered too
+        metadata: Metadata {
+            assignment: Some("-".to_string()),
+            due_date: Some("-".to_string()),
+            grader: Some("-".to_string()),
+        },
+        color: true,
+        explain_score: false,
+        failures_only: false,
+        order: Vec::new(),
+    };
+    render(template, &sample, &options).map(|_| ())
+}
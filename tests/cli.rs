@@ -0,0 +1,476 @@
+//! The text report as the binary prints it
+use quickgrade::{Config, Sidecar};
+use std::io::Write;
+use std::path::Path;
+use std::process::{Command, Output, Stdio};
+
+fn run_in(dir: &Path, args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_quickgrade"))
+        .current_dir(dir)
+        .args(args)
+        .output()
+        .expect("can't run quickgrade")
+}
+
+// Runs it in the crate root with `input` piped to stdin, what the grader types
+fn answering(args: &[&str], input: &str) -> Output {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_quickgrade"))
+        .current_dir(env!("CARGO_MANIFEST_DIR"))
+        .args(args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .expect("can't run quickgrade");
+    // it may stop reading before the end
+    let _ = child.stdin.take().unwrap().write_all(input.as_bytes());
+    child.wait_with_output().unwrap()
+}
+
+fn quickgrade(args: &[&str]) -> String {
+    let output = run_in(Path::new(env!("CARGO_MANIFEST_DIR")), args);
+    assert!(output.status.success(), "{:?}", output);
+    String::from_utf8(output.stdout).unwrap()
+}
+
+#[test]
+fn failures_only_prints_just_the_score_for_a_perfect_essay() {
+    let report = quickgrade(&[
+        "tests/fixtures/clean.txt",
+        "--failures-only",
+        "--default-answer",
+        "yes",
+    ]);
+    assert_eq!(
+        report,
+        "#== === === === =#= === === === ==#\n100%(100%): Final score\n\n"
+    );
+}
+
+#[test]
+fn failures_only_keeps_the_failed_criteria() {
+    let report = quickgrade(&[
+        "tests/fixtures/misspelled.txt",
+        "--failures-only",
+        "--default-answer",
+        "yes",
+    ]);
+    assert_eq!(
+        report,
+        "0%(20%): No spelling mistakes\n#== === === === =#= === === === ==#\n80%(100%): Final score\n\n"
+    );
+}
+
+#[test]
+fn init_writes_the_files_once() {
+    let dir = std::env::temp_dir().join(format!("quickgrade-init-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    assert!(run_in(&dir, &["init", "--sample"]).status.success());
+    assert!(dir.join("quickgrade.toml").exists());
+    // grades the sample under the written config
+    let output = run_in(&dir, &["--default-answer", "yes"]);
+    assert!(output.status.success(), "{:?}", output);
+    assert!(
+        String::from_utf8(output.stdout)
+            .unwrap()
+            .contains("100%(100%): Final score")
+    );
+    assert!(!run_in(&dir, &["init"]).status.success());
+    assert!(run_in(&dir, &["init", "--force"]).status.success());
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn questions_prompt_gives_up_on_endless_junk() {
+    // more junk than it will ever read, it must stop asking on its own
+    let output = answering(&["tests/fixtures/clean.txt"], &"maybe\n".repeat(100));
+    assert!(output.status.success(), "{:?}", output);
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert_eq!(stderr.matches("Please answer y or n").count(), 5);
+    assert!(stderr.contains("no valid answer to the questions prompt in 5 tries"));
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(stdout.contains("questions in complete sentences (not answered)"));
+}
+
+#[test]
+fn questions_prompt_asks_again_until_it_gets_an_answer() {
+    let output = answering(&["tests/fixtures/clean.txt"], "maybe\nsure\n\nYES\nno\n");
+    assert!(output.status.success(), "{:?}", output);
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert_eq!(
+        stderr.matches("Please answer y or n").count(),
+        3,
+        "{}",
+        stderr
+    );
+    // YES is taken, the no after it is never read
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(stdout.contains("100%(100%): Final score"), "{}", stdout);
+}
+
+#[test]
+fn report_order_picks_and_orders_the_criteria() {
+    let report = quickgrade(&[
+        "tests/fixtures/misspelled.txt",
+        "--report-order",
+        "questions,spelling,no_such_criterion",
+        "--default-answer",
+        "yes",
+    ]);
+    assert_eq!(
+        report,
+        "20%(20%): Answered all the questions in complete sentences\n\
+         0%(20%): No spelling mistakes\n\
+         #== === === === =#= === === === ==#\n80%(100%): Final score\n\n"
+    );
+}
+
+#[test]
+fn lint_only_lists_the_mistakes_without_grading() {
+    let lints = quickgrade(&["tests/fixtures/misspelled.txt", "--lint-only"]);
+    let lines: Vec<&str> = lints.lines().collect();
+    assert_eq!(lines.len(), 2, "{}", lints);
+    assert!(lines[0].starts_with("tests/fixtures/misspelled.txt:1:4: 'vidoe': "));
+    assert!(lines[1].starts_with("tests/fixtures/misspelled.txt:1:54: 'slepe': "));
+    assert!(lines[1].contains("Replace with: “slept”"));
+    assert!(!lints.contains("Final score"));
+
+    let json: serde_json::Value = serde_json::from_str(&quickgrade(&[
+        "tests/fixtures/misspelled.txt",
+        "--lint-only",
+        "--format",
+        "json",
+    ]))
+    .unwrap();
+    assert_eq!(json["issues"][1]["column"], 54);
+    assert_eq!(json["issues"][1]["kind"], "Spelling");
+}
+
+#[test]
+fn skip_manual_if_below_skips_the_prompt_for_a_lost_cause() {
+    let output = run_in(
+        Path::new(env!("CARGO_MANIFEST_DIR")),
+        &[
+            "tests/fixtures/misspelled.txt",
+            "--skip-manual-if-below",
+            "90",
+        ],
+    );
+    assert!(output.status.success(), "{:?}", output);
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(!stderr.contains("questions answered?"), "{}", stderr);
+    let report = String::from_utf8(output.stdout).unwrap();
+    assert!(
+        report.contains("(not asked, the final score is 80% at most with it, 90% needed)"),
+        "{}",
+        report
+    );
+    // ungraded, not a pass: the other four share its weight
+    assert!(report.contains("75%(100%): Final score"), "{}", report);
+    let json: serde_json::Value = serde_json::from_str(&quickgrade(&[
+        "tests/fixtures/misspelled.txt",
+        "--skip-manual-if-below",
+        "90",
+        "--format",
+        "json",
+    ]))
+    .unwrap();
+    let questions = &json["criteria"][4];
+    assert_eq!(questions["name"], "questions");
+    assert_eq!(questions["status"], "n/a");
+    assert_eq!(questions["grade"], 0.0);
+    assert!(json.get("manual").is_none(), "{}", json);
+
+    // a passing score is still worth asking about
+    let report = quickgrade(&[
+        "tests/fixtures/clean.txt",
+        "--skip-manual-if-below",
+        "90",
+        "--default-answer",
+        "no",
+    ]);
+    assert!(report.contains("80%(100%): Final score"), "{}", report);
+}
+
+#[test]
+fn emit_sidecar_reads_back_the_same_result() {
+    let dir = std::env::temp_dir().join(format!("quickgrade-sidecar-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    std::fs::copy(
+        Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/misspelled.txt"),
+        dir.join("essay.txt"),
+    )
+    .unwrap();
+    let output = run_in(&dir, &["essay.txt", "--emit-sidecar"]);
+    assert!(output.status.success(), "{:?}", output);
+    let sidecar = Sidecar::read(&dir.join("essay.quickgrade.json")).unwrap();
+    std::fs::remove_dir_all(&dir).unwrap();
+    assert_eq!(sidecar.file, "essay.txt");
+    assert_eq!(sidecar.config_hash, Config::default().hash());
+    // the unanswered questions criterion stays out of the score
+    assert_eq!(sidecar.rubric.score_percent(), 75.0);
+    let rubric = serde_json::to_value(&sidecar.rubric).unwrap();
+    assert_eq!(rubric["criteria"][4]["status"], "n/a");
+    assert_eq!(rubric["errors"][1]["text"], "slepe");
+}
+
+#[test]
+fn jobs_grade_a_batch_the_same_in_the_same_order() {
+    let one = quickgrade(&["tests/fixtures", "--default-answer", "yes", "--jobs", "1"]);
+    let three = quickgrade(&["tests/fixtures", "--default-answer", "yes", "--jobs", "3"]);
+    assert_eq!(one, three);
+    assert!(one.starts_with("== tests/fixtures/clean.txt =="), "{}", one);
+
+    // without answers it still asks about each file, one at a time
+    let output = run_in(
+        Path::new(env!("CARGO_MANIFEST_DIR")),
+        &["tests/fixtures", "--jobs", "3"],
+    );
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert_eq!(
+        stderr.matches("questions answered?").count(),
+        6,
+        "{}",
+        stderr
+    );
+}
+
+#[test]
+fn input_encoding_decodes_other_encodings() {
+    let dir = std::env::temp_dir().join(format!("quickgrade-encoding-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    // "café" in Latin-1 isn't valid UTF-8
+    std::fs::write(
+        dir.join("latin1.txt"),
+        b"We met at the caf\xe9 to talk.\n\nhttps://youtu.be/abc\n",
+    )
+    .unwrap();
+    let output = run_in(&dir, &["latin1.txt", "--default-answer", "yes"]);
+    assert!(!output.status.success());
+    assert!(
+        String::from_utf8(output.stderr)
+            .unwrap()
+            .contains("not valid UTF-8")
+    );
+    let output = run_in(
+        &dir,
+        &["latin1.txt", "--input-encoding", "latin1", "--lint-only"],
+    );
+    assert!(output.status.success(), "{:?}", output);
+    assert!(String::from_utf8(output.stdout).unwrap().contains("'café'"));
+
+    // UTF-16 without a byte order mark has to be named
+    let utf16: Vec<u8> = "A clean essay.\n"
+        .encode_utf16()
+        .flat_map(u16::to_le_bytes)
+        .collect();
+    std::fs::write(dir.join("utf16.txt"), utf16).unwrap();
+    let output = run_in(
+        &dir,
+        &["utf16.txt", "--input-encoding", "utf-16le", "--lint-only"],
+    );
+    std::fs::remove_dir_all(&dir).unwrap();
+    assert!(output.status.success(), "{:?}", output);
+    assert!(output.stdout.is_empty(), "{:?}", output);
+    assert!(output.stderr.is_empty(), "{:?}", output);
+}
+
+#[test]
+fn dry_run_checks_the_inputs_without_grading() {
+    let output = run_in(
+        Path::new(env!("CARGO_MANIFEST_DIR")),
+        &["tests/fixtures/", "--dry-run"],
+    );
+    assert!(output.status.success(), "{:?}", output);
+    assert_eq!(
+        String::from_utf8(output.stdout).unwrap(),
+        "all checks passed: 6 inputs\n"
+    );
+    let output = run_in(
+        Path::new(env!("CARGO_MANIFEST_DIR")),
+        &["tests/fixtures/clean.txt", "missing.txt", "--dry-run"],
+    );
+    assert!(!output.status.success());
+    assert!(output.stdout.is_empty(), "{:?}", output);
+    assert!(
+        String::from_utf8(output.stderr)
+            .unwrap()
+            .contains("can't open missing.txt")
+    );
+}
+
+#[test]
+fn profile_times_each_rule_on_stderr() {
+    let output = run_in(
+        Path::new(env!("CARGO_MANIFEST_DIR")),
+        &[
+            "tests/fixtures/clean.txt",
+            "--default-answer",
+            "yes",
+            "--profile",
+            "folded",
+        ],
+    );
+    assert!(output.status.success(), "{:?}", output);
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    // folded stacks, self time in microseconds last
+    let spelling = stderr
+        .lines()
+        .find_map(|l| l.strip_prefix("quickgrade;lint;rules;Spelling "))
+        .expect("no Spelling frame");
+    assert!(spelling.parse::<u64>().is_ok(), "{}", stderr);
+    assert!(
+        String::from_utf8(output.stdout)
+            .unwrap()
+            .contains("Final score")
+    );
+}
+
+#[test]
+fn aggregate_grades_the_files_as_one() {
+    let report = quickgrade(&[
+        "tests/fixtures/clean.txt",
+        "tests/fixtures/misspelled.txt",
+        "--aggregate",
+        "--default-answer",
+        "yes",
+    ]);
+    // one report, no batch headers, the misspellings of the second file count
+    assert!(!report.contains("== tests/"), "{}", report);
+    assert!(report.starts_with(
+        "Graded together: tests/fixtures/clean.txt, tests/fixtures/misspelled.txt (34 words)\n"
+    ));
+    assert!(report.contains("0%(20%): No spelling mistakes\n"));
+    assert!(report.ends_with("80%(100%): Final score\n\n"));
+}
+
+#[test]
+fn a_panicking_rule_only_loses_its_own_mistakes() {
+    let output = Command::new(env!("CARGO_BIN_EXE_quickgrade"))
+        .current_dir(env!("CARGO_MANIFEST_DIR"))
+        .args(["--format", "json", "--default-answer", "yes"])
+        .args(["--list-exemption", "none", "tests/fixtures/list.txt"])
+        .env("QUICKGRADE_PANIC_RULE", "Sams Lint")
+        .output()
+        .expect("can't run quickgrade");
+    assert!(output.status.success(), "{:?}", output);
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(
+        stderr.contains("the Sams Lint rule failed on this text, its mistakes are left out"),
+        "{}",
+        stderr
+    );
+    let json: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(json["failed_rules"], serde_json::json!(["Sams Lint"]));
+    // the missing periods were its, the lowercase list items are another rule's
+    let criteria: Vec<&str> = json["errors"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|e| e["criterion"].as_str().unwrap())
+        .collect();
+    assert!(!criteria.is_empty() && criteria.iter().all(|c| *c == "capitalization"));
+}
+
+#[test]
+fn quickgradeignore_skips_the_files_it_lists() {
+    let dir = Path::new(env!("CARGO_MANIFEST_DIR"));
+    let graded = |args: &[&str]| {
+        let output = run_in(dir, args);
+        assert!(output.status.success(), "{:?}", output);
+        let stdout = String::from_utf8(output.stdout).unwrap();
+        let files: Vec<String> = stdout
+            .lines()
+            .filter_map(|l| l.strip_prefix("== tests/fixtures/ignored/"))
+            .map(|l| l.trim_end_matches(" ==").to_string())
+            .collect();
+        (files, String::from_utf8(output.stderr).unwrap())
+    };
+    // draft-1.txt and rubric.template.txt are in its .quickgradeignore
+    let (files, stderr) = graded(&["tests/fixtures/ignored", "--default-answer", "yes"]);
+    assert_eq!(files, ["essay.txt", "second.txt"]);
+    assert!(stderr.contains("skipped 2 excluded files"), "{}", stderr);
+    // --exclude adds to it
+    let (files, stderr) = graded(&[
+        "tests/fixtures/ignored",
+        "--exclude",
+        "second.txt",
+        "--default-answer",
+        "yes",
+    ]);
+    assert_eq!(files, ["essay.txt"]);
+    assert!(stderr.contains("skipped 3 excluded files"), "{}", stderr);
+    // a file named on the command line only goes by --exclude
+    let report = quickgrade(&[
+        "tests/fixtures/ignored/draft-1.txt",
+        "--default-answer",
+        "yes",
+    ]);
+    assert!(report.contains("80%(100%): Final score"), "{}", report);
+}
+
+#[test]
+fn input_format_gdoc_cleans_up_a_google_docs_export() {
+    // saved with "download as plain text": a byte order mark, \r\n, the
+    // heading underlined, trailing spaces and doubled blank lines
+    let lint = |format: &str| {
+        quickgrade(&[
+            "tests/fixtures/gdoc-export.txt",
+            "--input-format",
+            format,
+            "--lint-only",
+        ])
+    };
+    let plain = lint("plain");
+    assert!(plain.contains(":2:1: '=============='"), "{}", plain);
+    assert!(plain.contains(":5:54: 'slepe'"), "{}", plain);
+    let gdoc = lint("gdoc");
+    let lines: Vec<&str> = gdoc.lines().collect();
+    assert_eq!(lines.len(), 2, "{}", gdoc);
+    assert!(lines[0].starts_with("tests/fixtures/gdoc-export.txt:1:1: 'Why Cats Sleep'"));
+    assert!(lines[1].starts_with("tests/fixtures/gdoc-export.txt:3:54: 'slepe'"));
+
+    let report = quickgrade(&[
+        "tests/fixtures/gdoc-export.txt",
+        "--input-format",
+        "gdoc",
+        "--require-title",
+        "--min-paragraphs",
+        "4",
+        "--default-answer",
+        "yes",
+    ]);
+    assert!(report.contains("(4 found)"), "{}", report);
+    assert!(report.contains("(found 'Why Cats Sleep')"), "{}", report);
+}
+
+#[test]
+fn questions_prompt_takes_a_score_or_yes_and_no() {
+    let questions = |answer: &str| {
+        let output = answering(&["tests/fixtures/clean.txt"], answer);
+        assert!(output.status.success(), "{:?}", output);
+        let stdout = String::from_utf8(output.stdout).unwrap();
+        let line = stdout
+            .lines()
+            .find(|l| l.contains("questions in complete sentences"))
+            .unwrap()
+            .to_string();
+        let score = stdout.lines().rev().find(|l| !l.is_empty()).unwrap();
+        (line, score.to_string())
+    };
+    let (line, score) = questions("75\n");
+    assert!(line.starts_with("15%(20%): "), "{}", line);
+    assert_eq!(score, "95%(100%): Final score");
+    let (line, score) = questions("40%\n");
+    assert!(line.starts_with("8%(20%): "), "{}", line);
+    assert_eq!(score, "88%(100%): Final score");
+    // out of range is asked again
+    let (line, _) = questions("150\n100\n");
+    assert!(line.starts_with("20%(20%): "), "{}", line);
+    let (line, score) = questions("y\n");
+    assert!(line.starts_with("20%(20%): "), "{}", line);
+    assert_eq!(score, "100%(100%): Final score");
+    let (line, score) = questions("No\n");
+    assert!(line.starts_with("0%(20%): "), "{}", line);
+    assert_eq!(score, "80%(100%): Final score");
+}
@@ -0,0 +1,1409 @@
+//! Known essays through the library, checked against the JSON report so a
+//! harper upgrade that changes what gets flagged shows up here
+use quickgrade::{
+    BritishSpellings, Config, Contractions, Expected, HyphenatedWords, KeyAnswer, KeywordSet,
+    LINTERS, LinterPreset, Linters, ListExemption, Rounding, Rubric, TitleStrictness, TokenClass,
+    Voice, grade_sections, grade_text,
+};
+use serde_json::Value;
+
+fn fixture(name: &str) -> String {
+    let path = format!("{}/tests/fixtures/{}", env!("CARGO_MANIFEST_DIR"), name);
+    std::fs::read_to_string(&path).unwrap_or_else(|e| panic!("can't read {}: {}", path, e))
+}
+
+fn report(rubric: &Rubric) -> Value {
+    serde_json::to_value(rubric).unwrap()
+}
+
+// (criterion, grade) in report order
+fn grades(rubric: &Rubric) -> Vec<(String, f64)> {
+    report(rubric)["criteria"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|c| {
+            (
+                c["name"].as_str().unwrap().to_string(),
+                c["grade"].as_f64().unwrap(),
+            )
+        })
+        .collect()
+}
+
+// The default config with the settings a test needs changed
+fn configured(change: impl FnOnce(&mut Config)) -> Config {
+    let mut config = Config::default();
+    change(&mut config);
+    config
+}
+
+// A criterion of the report, by name so adding optional criteria doesn't
+// shift what a test looks at
+fn criterion(rubric: &Rubric, name: &str) -> Value {
+    report(rubric)["criteria"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|c| c["name"] == name)
+        .unwrap_or_else(|| panic!("no {} criterion", name))
+        .clone()
+}
+
+// A criterion's grade and detail
+fn graded(rubric: &Rubric, name: &str) -> (f64, String) {
+    let criterion = criterion(rubric, name);
+    (
+        criterion["grade"].as_f64().unwrap(),
+        criterion["detail"].as_str().unwrap().to_string(),
+    )
+}
+
+fn expect(rubric: &Rubric, expected: &[(&str, f64)]) {
+    let expected: Vec<(String, f64)> = expected
+        .iter()
+        .map(|(name, grade)| (name.to_string(), *grade))
+        .collect();
+    assert_eq!(grades(rubric), expected);
+}
+
+#[test]
+fn clean_essay_gets_full_marks() {
+    let rubric = Rubric::from_string_with_answers(&fixture("clean.txt"), &Config::default(), true);
+    expect(
+        &rubric,
+        &[
+            ("link", 1.0),
+            ("spelling", 1.0),
+            ("punctuation", 1.0),
+            ("capitalization", 1.0),
+            ("questions", 1.0),
+        ],
+    );
+    assert!(report(&rubric)["errors"].as_array().unwrap().is_empty());
+    assert_eq!(rubric.score_percent(), 100.0);
+}
+
+#[test]
+fn misspellings_fail_spelling() {
+    let rubric =
+        Rubric::from_string_with_answers(&fixture("misspelled.txt"), &Config::default(), true);
+    expect(
+        &rubric,
+        &[
+            ("link", 1.0),
+            ("spelling", 0.0),
+            ("punctuation", 1.0),
+            ("capitalization", 1.0),
+            ("questions", 1.0),
+        ],
+    );
+    let report = report(&rubric);
+    let mut misspelled: Vec<&str> = report["errors"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .filter(|e| e["criterion"] == "spelling")
+        .map(|e| e["text"].as_str().unwrap())
+        .collect();
+    misspelled.sort();
+    assert_eq!(misspelled, ["slepe", "vidoe"]);
+    assert_eq!(rubric.score_percent(), 80.0);
+}
+
+#[test]
+fn missing_link_fails_link() {
+    let rubric =
+        Rubric::from_string_with_answers(&fixture("no_link.txt"), &Config::default(), true);
+    expect(
+        &rubric,
+        &[
+            ("link", 0.0),
+            ("spelling", 1.0),
+            ("punctuation", 1.0),
+            ("capitalization", 1.0),
+            ("questions", 1.0),
+        ],
+    );
+    assert_eq!(rubric.score_percent(), 80.0);
+}
+
+#[test]
+fn empty_essay_only_gets_the_lint_criteria() {
+    let rubric = Rubric::from_string_with_answers(&fixture("empty.txt"), &Config::default(), false);
+    expect(
+        &rubric,
+        &[
+            ("link", 0.0),
+            ("spelling", 1.0),
+            ("punctuation", 1.0),
+            ("capitalization", 1.0),
+            ("questions", 0.0),
+        ],
+    );
+    assert_eq!(rubric.score_percent(), 60.0);
+}
+
+#[test]
+fn grade_text_estimates_questions_under_auto_questions() {
+    let config = configured(|c| c.auto_questions = true);
+    let rubric = grade_text(&fixture("clean.txt"), &config);
+    assert_eq!(report(&rubric)["manual"][0]["estimated"], true);
+    assert_eq!(rubric.score_percent(), 100.0);
+}
+
+#[test]
+fn grade_text_leaves_questions_out_without_an_answer() {
+    let rubric = grade_text(&fixture("no_link.txt"), &Config::default());
+    assert_eq!(graded(&rubric, "questions").1, "not answered");
+    // link failed, the other three passed, questions isn't scored
+    assert_eq!(rubric.score_percent(), 75.0);
+}
+
+#[test]
+fn list_items_skip_capitalization_by_default() {
+    let rubric = Rubric::from_string_with_answers(&fixture("list.txt"), &Config::default(), true);
+    expect(
+        &rubric,
+        &[
+            ("link", 1.0),
+            ("spelling", 1.0),
+            ("punctuation", 0.0),
+            ("capitalization", 1.0),
+            ("questions", 1.0),
+        ],
+    );
+}
+
+#[test]
+fn list_items_can_skip_punctuation_too() {
+    let config = configured(|c| c.list_exemption = ListExemption::Both);
+    let rubric = Rubric::from_string_with_answers(&fixture("list.txt"), &config, true);
+    assert!(report(&rubric)["errors"].as_array().unwrap().is_empty());
+    assert_eq!(rubric.score_percent(), 100.0);
+}
+
+#[test]
+fn list_items_can_be_graded() {
+    let config = configured(|c| c.list_exemption = ListExemption::None);
+    let rubric = Rubric::from_string_with_answers(&fixture("list.txt"), &config, true);
+    expect(
+        &rubric,
+        &[
+            ("link", 1.0),
+            ("spelling", 1.0),
+            ("punctuation", 0.0),
+            ("capitalization", 0.0),
+            ("questions", 1.0),
+        ],
+    );
+}
+
+// The default five plus a grammar category, which comes after spelling
+fn with_grammar(priority: &str) -> Config {
+    toml::from_str(&format!(
+        r#"
+        {}
+        [[categories]]
+        name = "link"
+        check = "link"
+        [[categories]]
+        name = "spelling"
+        lints = ["spelling"]
+        [[categories]]
+        name = "grammar"
+        lints = ["Grammar"]
+        [[categories]]
+        name = "questions"
+        check = "questions"
+        "#,
+        priority
+    ))
+    .unwrap()
+}
+
+fn criteria_of_errors(rubric: &Rubric) -> Vec<String> {
+    report(rubric)["errors"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|e| e["criterion"].as_str().unwrap().to_string())
+        .collect()
+}
+
+#[test]
+fn lint_priority_picks_the_first_category_in_the_chain() {
+    let config = with_grammar("lint_priority = { Spelling = [\"grammar\", \"spelling\"] }");
+    let rubric = Rubric::from_string_with_answers(&fixture("misspelled.txt"), &config, true);
+    assert_eq!(criteria_of_errors(&rubric), ["grammar", "grammar"]);
+    expect(
+        &rubric,
+        &[
+            ("link", 1.0),
+            ("spelling", 1.0),
+            ("grammar", 0.0),
+            ("questions", 1.0),
+        ],
+    );
+}
+
+#[test]
+fn lint_priority_skips_categories_the_config_lacks() {
+    let config = with_grammar("lint_priority = { Spelling = [\"typos\", \"spelling\"] }");
+    let rubric = Rubric::from_string_with_answers(&fixture("misspelled.txt"), &config, true);
+    assert_eq!(criteria_of_errors(&rubric), ["spelling", "spelling"]);
+}
+
+#[test]
+fn lint_priority_falls_back_to_report_order() {
+    // none of the chain is there, so the first category taking spelling wins
+    let config = with_grammar("lint_priority = { Spelling = [\"typos\"] }");
+    let rubric = Rubric::from_string_with_answers(&fixture("misspelled.txt"), &config, true);
+    assert_eq!(criteria_of_errors(&rubric), ["spelling", "spelling"]);
+    let rubric =
+        Rubric::from_string_with_answers(&fixture("misspelled.txt"), &with_grammar(""), true);
+    assert_eq!(criteria_of_errors(&rubric), ["spelling", "spelling"]);
+}
+
+fn paragraph_balance(text: &str) -> f64 {
+    let config = configured(|c| c.max_paragraph_share = Some(0.5));
+    graded(&grade_text(text, &config), "paragraph_balance").0
+}
+
+#[test]
+fn balanced_paragraphs_pass() {
+    let text = "The first paragraph has six words.\n\n\
+        The second one has six too.\n\n\
+        And the third has six words.\n";
+    assert_eq!(paragraph_balance(text), 1.0);
+}
+
+#[test]
+fn one_dominant_paragraph_fails() {
+    let text = "A short one.\n\n\
+        This paragraph goes on and on, with far more words than \
+        the other two put together, so it dominates the essay.\n\n\
+        Another short one.\n";
+    assert_eq!(paragraph_balance(text), 0.0);
+    assert_eq!(paragraph_balance("Only one paragraph here.\n"), 0.0);
+}
+
+#[test]
+fn mixed_line_endings_keep_line_numbers() {
+    let config = configured(|c| {
+        c.forbidden_words = vec!["very".to_string()];
+        c.min_paragraphs = 3;
+    });
+    // a lone \r used to run the second and third lines together
+    let text =
+        "First line.\r\nSecond line.\rIt is very good.\n\nLast paragraph.\r\n\r\nOne more.\n";
+    let rubric = Rubric::from_reader(text.as_bytes(), &config).unwrap();
+    assert_eq!(graded(&rubric, "forbidden_words").1, "'very' at 3:7");
+    assert_eq!(graded(&rubric, "paragraphs").1, "3 found");
+}
+
+fn complete_paragraphs(text: &str) -> (f64, String) {
+    let config = configured(|c| c.require_complete_paragraphs = true);
+    graded(&grade_text(text, &config), "complete_paragraphs")
+}
+
+#[test]
+fn paragraphs_with_complete_sentences_pass() {
+    let text = "The cat sat on the mat.\n\nShe wrote a long letter to her friend.\n";
+    assert_eq!(complete_paragraphs(text), (1.0, String::new()));
+}
+
+#[test]
+fn fragment_paragraphs_are_reported() {
+    let text = "The cat sat on the mat.\n\nRunning down the street.\n\nA big red house.\n";
+    assert_eq!(
+        complete_paragraphs(text),
+        (0.0, "paragraphs 2, 3 have none".to_string())
+    );
+}
+
+fn readability_words(text: &str, hyphenated: HyphenatedWords, contractions: Contractions) -> u64 {
+    let config = configured(|c| {
+        c.min_grade_level = Some(0.0);
+        c.hyphenated_words = hyphenated;
+        c.contractions = contractions;
+    });
+    report(&grade_text(text, &config))["readability"]["words"]
+        .as_u64()
+        .unwrap()
+}
+
+#[test]
+fn hyphenated_words_count_per_part_or_once() {
+    let text = "My mother-in-law is a well-known cook.\n";
+    assert_eq!(
+        readability_words(text, HyphenatedWords::Parts, Contractions::One),
+        9
+    );
+    assert_eq!(
+        readability_words(text, HyphenatedWords::One, Contractions::One),
+        6
+    );
+    // dashes with spaces around them or doubled up don't join words
+    let text = "It rained - a lot -- all day.\n";
+    assert_eq!(
+        readability_words(text, HyphenatedWords::One, Contractions::One),
+        6
+    );
+}
+
+#[test]
+fn contractions_count_once_or_twice() {
+    let text = "They don't know it's the students' turn.\n";
+    assert_eq!(
+        readability_words(text, HyphenatedWords::Parts, Contractions::One),
+        7
+    );
+    // a trailing possessive apostrophe isn't a contraction
+    assert_eq!(
+        readability_words(text, HyphenatedWords::Parts, Contractions::Two),
+        9
+    );
+}
+
+#[test]
+fn sentence_length_follows_the_word_count_policy() {
+    let text = "My mother-in-law is a well-known cook.\n";
+    let grade = |hyphenated| {
+        let config = configured(|c| {
+            c.max_sentence_words = 6;
+            c.max_long_sentences = 0;
+            c.hyphenated_words = hyphenated;
+        });
+        graded(&grade_text(text, &config), "sentence_length").0
+    };
+    assert_eq!(grade(HyphenatedWords::One), 1.0);
+    assert_eq!(grade(HyphenatedWords::Parts), 0.0);
+}
+
+// Harper reads most links as links, but loses track of these
+const LINKS: &str = "The video \"https://youtu.be/AbCdEfG\" was good.\n\
+    My link:https://www.youtube.com/watch?v=Q1w2E3r4 works.\n\
+    Link https://www.youtube.com/@SomeChannel/videos.\n";
+
+#[test]
+fn links_can_trip_the_spelling_lints() {
+    let rubric = Rubric::from_string_with_answers(LINKS, &Config::default(), true);
+    expect(
+        &rubric,
+        &[
+            ("link", 1.0),
+            ("spelling", 0.0),
+            ("punctuation", 1.0),
+            ("capitalization", 0.0),
+            ("questions", 1.0),
+        ],
+    );
+}
+
+#[test]
+fn stripped_links_are_not_linted_but_still_count() {
+    let config = configured(|c| c.strip_urls_before_linting = true);
+    let rubric = Rubric::from_string_with_answers(LINKS, &config, true);
+    assert!(report(&rubric)["errors"].as_array().unwrap().is_empty());
+    assert_eq!(rubric.score_percent(), 100.0);
+}
+
+fn voice(text: &str, voice: Voice, max_first_person: usize) -> (f64, String) {
+    let config = configured(|c| {
+        c.voice = Some(voice);
+        c.max_first_person = max_first_person;
+    });
+    graded(&grade_text(text, &config), "voice")
+}
+
+const FIRST_PERSON: &str = "I think my essay is good. We wrote it for our class.\n";
+const THIRD_PERSON: &str =
+    "The author argues that cities need more parks. She cites two studies.\n";
+
+#[test]
+fn third_person_voice_counts_first_person_pronouns() {
+    assert_eq!(
+        voice(THIRD_PERSON, Voice::Third, 0),
+        (1.0, "no first-person pronouns".to_string())
+    );
+    assert_eq!(
+        voice(FIRST_PERSON, Voice::Third, 0),
+        (0.0, "4 first-person pronouns (I, my, We, our)".to_string())
+    );
+    assert_eq!(voice(FIRST_PERSON, Voice::Third, 4).0, 1.0);
+}
+
+#[test]
+fn first_person_voice_needs_a_first_person_pronoun() {
+    assert_eq!(voice(FIRST_PERSON, Voice::First, 0).0, 1.0);
+    assert_eq!(voice(THIRD_PERSON, Voice::First, 0).0, 0.0);
+}
+
+// The same misspelling as the first word and as the last
+const EARLY: &str = "Teh video explains how plants turn sunlight into food.\n\nThe leaves hold chlorophyll, which catches the light.\n\nWater and air are needed as well, and the plant makes sugar.\n\nhttps://youtu.be/abc\n";
+const LATE: &str = "The video explains how plants turn sunlight into food.\n\nThe leaves hold chlorophyll, which catches the light.\n\nWater and air are needed as well, and the plant makes sugar.\n\nhttps://youtu.be/abc Teh\n";
+
+// Two spelling mistakes allowed, and with early_error_weight one at the very
+// start counting that many times
+fn early_weighted(weight: Option<f32>) -> Config {
+    configured(|c| {
+        let spelling = c.categories.iter_mut().find(|c| c.name == "spelling");
+        spelling.unwrap().rate = 0.05;
+        c.early_error_weight = weight;
+    })
+}
+
+#[test]
+fn early_mistakes_weigh_more() {
+    let early = Rubric::from_string_with_answers(EARLY, &early_weighted(Some(3.0)), true);
+    let late = Rubric::from_string_with_answers(LATE, &early_weighted(Some(3.0)), true);
+    let spelling = |rubric: &Rubric| graded(rubric, "spelling").0;
+    assert_eq!(spelling(&early), 0.0);
+    assert_eq!(spelling(&late), 1.0);
+    let weight = |rubric: &Rubric| report(rubric)["errors"][0]["weight"].as_f64().unwrap();
+    assert_eq!(weight(&early), 3.0);
+    assert!(weight(&late) < 1.1);
+}
+
+#[test]
+fn mistakes_weigh_the_same_without_early_error_weight() {
+    let config = early_weighted(None);
+    for text in [EARLY, LATE] {
+        let rubric = Rubric::from_string_with_answers(text, &config, true);
+        assert_eq!(graded(&rubric, "spelling").0, 1.0);
+        assert!(report(&rubric)["errors"][0].get("weight").is_none());
+    }
+}
+
+const PROMPT: &str = "Watch the video and explain how plants make food from sunlight\nUse complete sentences and link the video\n";
+// The prompt pasted above the answer, its lines don't end in periods
+const WITH_PROMPT: &str = "Watch the video and explain how plants make food from sunlight\nUse complete sentences and link the video\n\nThe video explains how plants turn sunlight into food.\n\nhttps://youtu.be/abc\n";
+
+#[test]
+fn pasted_prompt_is_graded_without_a_reference() {
+    let rubric = Rubric::from_string_with_answers(WITH_PROMPT, &Config::default(), true);
+    assert_eq!(graded(&rubric, "punctuation").0, 0.0);
+    assert!(report(&rubric).get("prompt_stripped").is_none());
+}
+
+#[test]
+fn pasted_prompt_matching_the_prompt_file_is_left_out() {
+    let config = configured(|c| c.prompt = Some(PROMPT.to_string()));
+    let rubric = Rubric::from_string_with_answers(WITH_PROMPT, &config, true);
+    assert_eq!(graded(&rubric, "punctuation").0, 1.0);
+    assert_eq!(
+        report(&rubric)["prompt_stripped"],
+        serde_json::json!({"lines": 2, "words": 18})
+    );
+}
+
+#[test]
+fn prompt_marker_leaves_out_everything_above_it() {
+    let config = configured(|c| c.prompt_marker = Some("^-{3,}$".to_string()));
+    let text = WITH_PROMPT.replacen("\n\n", "\n---\n", 1);
+    let rubric = Rubric::from_string_with_answers(&text, &config, true);
+    assert_eq!(graded(&rubric, "punctuation").0, 1.0);
+    assert_eq!(
+        report(&rubric)["prompt_stripped"],
+        serde_json::json!({"lines": 3, "words": 19})
+    );
+}
+
+const SYMBOLS: &str = "Plants need H2O and CO2 to grow.\nThe kit costs USD5 or about 4 EUR.\n\nhttps://youtu.be/abc\n";
+
+fn misspelled(rubric: &Rubric) -> Vec<String> {
+    report(rubric)["errors"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .filter(|e| e["criterion"] == "spelling")
+        .map(|e| e["text"].as_str().unwrap().to_string())
+        .collect()
+}
+
+#[test]
+fn numbers_and_symbols_count_as_misspellings_by_default() {
+    let rubric = Rubric::from_string_with_answers(SYMBOLS, &Config::default(), true);
+    assert_eq!(misspelled(&rubric), ["H2O", "CO2", "USD5", "EUR"]);
+    assert!(report(&rubric).get("ignored_tokens").is_none());
+}
+
+#[test]
+fn spelling_ignore_only_drops_its_token_classes() {
+    let config = configured(|c| c.spelling_ignore = vec![TokenClass::Alphanumeric]);
+    let rubric = Rubric::from_string_with_answers(SYMBOLS, &config, true);
+    assert_eq!(misspelled(&rubric), ["EUR"]);
+    assert_eq!(
+        report(&rubric)["ignored_tokens"],
+        serde_json::json!(["H2O", "CO2", "USD5"])
+    );
+    let config =
+        configured(|c| c.spelling_ignore = vec![TokenClass::Alphanumeric, TokenClass::Currency]);
+    let rubric = Rubric::from_string_with_answers(SYMBOLS, &config, true);
+    assert!(misspelled(&rubric).is_empty());
+    assert_eq!(graded(&rubric, "spelling").0, 1.0);
+}
+
+#[test]
+fn token_classes() {
+    for token in ["3rd", "1,000", "1990s", "50%"] {
+        assert!(TokenClass::Numbers.matches(token), "{}", token);
+    }
+    for token in ["$5", "EUR", "usd5", "10€"] {
+        assert!(TokenClass::Currency.matches(token), "{}", token);
+    }
+    for token in ["H2O", "mp3", "COVID-19"] {
+        assert!(TokenClass::Alphanumeric.matches(token), "{}", token);
+    }
+    assert!(!TokenClass::Numbers.matches("H2O"));
+    assert!(!TokenClass::Currency.matches("Euro"));
+    assert!(!TokenClass::Alphanumeric.matches("1,000"));
+}
+
+#[test]
+fn init_config_template_is_the_default_config() {
+    let template = include_str!("../templates/quickgrade.toml");
+    let config: Config = toml::from_str(template).unwrap();
+    assert_eq!(
+        serde_json::to_value(&config).unwrap(),
+        serde_json::to_value(Config::default()).unwrap()
+    );
+}
+
+const BRITISH: &str = "My favourite colour is green.\n\nhttps://youtu.be/abc\n";
+const MIXED: &str = "My favourite colour is green, the color of grass.\n\nhttps://youtu.be/abc\n";
+
+#[test]
+fn british_spellings_are_mistakes_by_default() {
+    let rubric = Rubric::from_string_with_answers(BRITISH, &Config::default(), true);
+    assert_eq!(misspelled(&rubric), ["favourite", "colour"]);
+}
+
+#[test]
+fn accepted_british_spellings_dont_count() {
+    for text in [BRITISH, MIXED] {
+        let config = configured(|c| c.british_spellings = BritishSpellings::Accepted);
+        let rubric = Rubric::from_string_with_answers(text, &config, true);
+        assert!(misspelled(&rubric).is_empty());
+        assert_eq!(
+            report(&rubric)["british_accepted"],
+            serde_json::json!(["favourite", "colour"])
+        );
+    }
+}
+
+#[test]
+fn consistent_british_spellings_fail_when_mixed() {
+    let config = configured(|c| c.british_spellings = BritishSpellings::Consistent);
+    let rubric = Rubric::from_string_with_answers(BRITISH, &config, true);
+    assert!(misspelled(&rubric).is_empty());
+    assert_eq!(graded(&rubric, "consistent_spelling").0, 1.0);
+    let rubric = Rubric::from_string_with_answers(MIXED, &config, true);
+    assert!(misspelled(&rubric).is_empty());
+    assert_eq!(
+        graded(&rubric, "consistent_spelling"),
+        (
+            0.0,
+            "British favourite, colour and American color".to_string()
+        )
+    );
+}
+
+const FEW_TRANSITIONS: &str = "Plants need light. They make sugar from it. However, they also need water.\n\nhttps://youtu.be/abc\n";
+const MANY_TRANSITIONS: &str = "Plants need light. For example, they make sugar from it. However, they also need water. \
+Therefore, dry plants wilt. In addition, they need air. However, light matters most.\n\nhttps://youtu.be/abc\n";
+
+#[test]
+fn few_transitions_get_partial_credit() {
+    let config = configured(|c| c.min_transitions = 4);
+    let rubric = Rubric::from_string_with_answers(FEW_TRANSITIONS, &config, true);
+    assert_eq!(
+        graded(&rubric, "transitions"),
+        (0.25, "1 found: however".to_string())
+    );
+}
+
+#[test]
+fn distinct_transitions_count_once() {
+    let config = configured(|c| c.min_transitions = 4);
+    let rubric = Rubric::from_string_with_answers(MANY_TRANSITIONS, &config, true);
+    assert_eq!(
+        graded(&rubric, "transitions"),
+        (
+            1.0,
+            "4 found: for example, however, therefore, in addition".to_string()
+        )
+    );
+    let config = configured(|c| {
+        c.min_transitions = 2;
+        c.transition_words = vec!["in addition".to_string()];
+    });
+    let rubric = Rubric::from_string_with_answers(MANY_TRANSITIONS, &config, true);
+    assert_eq!(graded(&rubric, "transitions").0, 0.5);
+}
+
+#[test]
+fn decomposed_accents_grade_like_precomposed() {
+    // "résumé", "naïve" and "piñata" spelled with combining marks split into
+    // misspelled pieces unless they're composed first
+    let precomposed =
+        "Her r\u{e9}sum\u{e9} was na\u{ef}ve about the pi\u{f1}ata.\n\nhttps://youtu.be/abc\n";
+    let decomposed = "Her re\u{301}sume\u{301} was nai\u{308}ve about the pin\u{303}ata.\n\nhttps://youtu.be/abc\n";
+    let config = Config::default();
+    let expected = Rubric::from_string_with_answers(precomposed, &config, true);
+    let rubric = Rubric::from_string_with_answers(decomposed, &config, true);
+    assert_eq!(report(&rubric)["errors"], report(&expected)["errors"]);
+    assert_eq!(grades(&rubric), grades(&expected));
+    assert_eq!(rubric.score_percent(), 100.0);
+}
+
+#[test]
+fn linter_presets_pick_the_rules() {
+    assert_eq!(Linters::default().rules(), LINTERS);
+    assert_eq!(
+        Linters::preset(LinterPreset::SpellingOnly).rules(),
+        ["Spelling"]
+    );
+    let mechanics = Linters::preset(LinterPreset::Mechanics)
+        .enable("AnA")
+        .disable("CommaFixes");
+    let rules = mechanics.rules();
+    assert!(rules.contains(&"AnA") && rules.contains(&"SentenceCapitalization"));
+    assert!(!rules.contains(&"CommaFixes") && !rules.contains(&"HopHope"));
+    assert_eq!(
+        Linters::only(["Spelling", "AnA"]).disable("AnA").rules(),
+        ["Spelling"]
+    );
+    assert!(Linters::only(["Speling"]).check().is_err());
+}
+
+#[test]
+fn disabled_linters_find_nothing() {
+    let config = configured(|c| c.linters = Linters::preset(LinterPreset::SpellingOnly));
+    // only the missing periods and lowercase list items are wrong with it
+    let rubric = Rubric::from_string_with_answers(&fixture("list.txt"), &config, true);
+    assert!(report(&rubric)["errors"].as_array().unwrap().is_empty());
+    assert_eq!(rubric.score_percent(), 100.0);
+
+    let config = configured(|c| c.linters = Linters::default().disable("Spelling"));
+    let rubric = Rubric::from_string_with_answers(&fixture("misspelled.txt"), &config, true);
+    assert_eq!(graded(&rubric, "spelling").0, 1.0);
+}
+
+const QUOTES: &str =
+    "I don't think it's fair. The students' books weren't there and we couldn't find 'em. \
+Rock 'n' roll isn't dead, y'all, it's back since the '90s. James' dog didn't care.
+
+She called it 'art' and he said \"no way.\" Then she said, 'fine.'
+
+https://youtu.be/abc
+";
+
+#[test]
+fn apostrophes_arent_unclosed_quotes() {
+    let config = configured(|c| c.require_balanced_quotes = true);
+    let rubric = Rubric::from_string_with_answers(QUOTES, &config, true);
+    assert_eq!(graded(&rubric, "quotes").0, 1.0);
+}
+
+#[test]
+fn an_unclosed_quote_fails() {
+    let text = QUOTES.replace("https://", "He said \"stop and went home.\n\nhttps://");
+    let config = configured(|c| c.require_balanced_quotes = true);
+    let rubric = Rubric::from_string_with_answers(&text, &config, true);
+    assert_eq!(
+        graded(&rubric, "quotes"),
+        (0.0, "unclosed at 5:9".to_string())
+    );
+}
+
+#[test]
+fn utf16_is_decoded_by_its_byte_order_mark() {
+    let path = format!(
+        "{}/tests/fixtures/encodings/clean-utf16le.txt",
+        env!("CARGO_MANIFEST_DIR")
+    );
+    let bytes = std::fs::read(path).unwrap();
+    let config = configured(|c| c.auto_questions = true);
+    let rubric = Rubric::from_reader(bytes.as_slice(), &config).unwrap();
+    let expected = grade_text(&fixture("clean.txt"), &config);
+    assert_eq!(report(&rubric)["errors"], report(&expected)["errors"]);
+    assert_eq!(rubric.score_percent(), 100.0);
+}
+
+const ANSWERS: &str =
+    "Q1: Plants turn sunlight into sugar. They take in carbon dioxide through their leaves. \
+The oxygen they give off is what we breathe.
+Q2: Because of the sun. Because of the sun.
+
+https://youtu.be/abc
+";
+
+fn per_answer(min: usize) -> Config {
+    configured(|c| {
+        c.split_on = Some(r"^Q\d+:".to_string());
+        c.min_sentences_per_answer = min;
+    })
+}
+
+#[test]
+fn a_stub_answer_fails_and_is_named() {
+    let rubric = Rubric::from_string_with_answers(ANSWERS, &per_answer(2), true);
+    assert_eq!(
+        graded(&rubric, "answer_sentences"),
+        (0.0, "1 of 2 answers short: 'Q2:' has 1".to_string())
+    );
+}
+
+#[test]
+fn developed_answers_pass() {
+    let text = ANSWERS.replace(
+        "Because of the sun. Because of the sun.",
+        "Because of the sun. Without it nothing would grow.",
+    );
+    let rubric = Rubric::from_string_with_answers(&text, &per_answer(2), true);
+    assert_eq!(
+        graded(&rubric, "answer_sentences"),
+        (1.0, "2 answers".to_string())
+    );
+}
+
+#[test]
+fn statuses_tell_pass_fail_and_not_evaluated_apart() {
+    // no answer for the questions and no auto_questions leaves them unevaluated
+    let rubric = grade_text(&fixture("misspelled.txt"), &Config::default());
+    let json = report(&rubric);
+    let statuses: Vec<&str> = json["criteria"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|c| c["status"].as_str().unwrap())
+        .collect();
+    assert_eq!(statuses, ["pass", "fail", "pass", "pass", "n/a"]);
+    assert_eq!(criterion(&rubric, "questions")["grade"], 0.0);
+    // and reads back the same, the unevaluated one still out of the score
+    let read: Rubric = serde_json::from_value(json.clone()).unwrap();
+    assert_eq!(report(&read)["criteria"], json["criteria"]);
+    assert_eq!(read.score_percent(), rubric.score_percent());
+}
+
+#[test]
+fn changed_only_counts_the_mistakes_on_changed_lines() {
+    let previous = fixture("misspelled.txt");
+    let draft = format!("{}\nI wrote a secnd paragraph.\n", previous);
+    let config = configured(|c| c.previous = Some(previous));
+    let rubric = Rubric::from_string_with_answers(&draft, &config, true);
+    let errors = report(&rubric)["errors"].clone();
+    let texts: Vec<&str> = errors
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|e| e["text"].as_str().unwrap())
+        .collect();
+    assert_eq!(texts, ["secnd"]);
+    // fixing one old mistake changes its line, so the one left there counts again
+    let fixed = draft.replace("vidoe", "video");
+    let rubric = Rubric::from_string_with_answers(&fixed, &config, true);
+    assert_eq!(report(&rubric)["errors"][0]["text"], "slepe");
+    assert_eq!(report(&rubric)["errors"].as_array().unwrap().len(), 2);
+}
+
+const CAPS: &str =
+    "My video is about cats. they sleep a lot. they eat a lot too.\n\nhttps://youtu.be/abc\n";
+
+fn caps_tolerance(tolerance: usize) -> Config {
+    configured(|c| {
+        let caps = c.categories.iter_mut().find(|c| c.name == "capitalization");
+        caps.unwrap().tolerance = tolerance;
+    })
+}
+
+#[test]
+fn capitalization_slips_up_to_the_tolerance_pass() {
+    let rubric = Rubric::from_string_with_answers(CAPS, &caps_tolerance(2), true);
+    assert_eq!(
+        graded(&rubric, "capitalization"),
+        (1.0, "2 found, 2 allowed".to_string())
+    );
+}
+
+#[test]
+fn capitalization_slips_over_the_tolerance_fail() {
+    let text = CAPS.replace("My video", "my video");
+    let rubric = Rubric::from_string_with_answers(&text, &caps_tolerance(2), true);
+    assert_eq!(
+        graded(&rubric, "capitalization"),
+        (0.0, "3 found, 2 allowed".to_string())
+    );
+}
+
+const KEY: &str = "# unit 3 quiz
+gravity
+number: 9.81 +- 0.05
+any: mitochondria | powerhouse of the cell
+phrase: photosynthesis
+";
+
+#[test]
+fn answer_key_reports_found_and_missing_answers() {
+    let config = configured(|c| c.expected_answers = KeyAnswer::parse_key(KEY).unwrap());
+    let text = "Gravity pulls things down at 9.8 m/s. The powerhouse of the cell makes energy.\n\n\
+https://youtu.be/abc\n";
+    let rubric = Rubric::from_string_with_answers(text, &config, true);
+    assert_eq!(
+        graded(&rubric, "answer_key"),
+        (
+            0.75,
+            "found: gravity, 9.81 +- 0.05, mitochondria | powerhouse of the cell; missing: photosynthesis"
+                .to_string()
+        )
+    );
+}
+
+#[test]
+fn answer_key_lines_are_checked() {
+    let answers = KeyAnswer::parse_key("number: 1.5 ± 0.01\nany: a |").unwrap();
+    assert_eq!(
+        answers[0].expected,
+        Expected::Number {
+            value: 1.5,
+            tolerance: 0.01
+        }
+    );
+    assert_eq!(answers[1].expected, Expected::AnyOf(vec!["a".to_string()]));
+    assert_eq!(
+        KeyAnswer::parse_key("number: about 3").unwrap_err(),
+        "line 1: 'about 3' isn't a number"
+    );
+    assert!(KeyAnswer::parse_key("# nothing\n").is_err());
+}
+
+// The questions aren't answered so they're left out, the three passes are
+// worth 3 of 4.8 and the score is 62.5
+fn half_point(rounding: Rounding) -> Config {
+    configured(|c| {
+        c.rounding = rounding;
+        let spelling = c.categories.iter_mut().find(|c| c.name == "spelling");
+        spelling.unwrap().weight = 1.8;
+    })
+}
+
+#[test]
+fn rounding_modes_at_a_half_point() {
+    let text = fixture("misspelled.txt");
+    let score = |rounding| grade_text(&text, &half_point(rounding)).score_percent();
+    assert_eq!(score(Rounding::Nearest), 63.0);
+    assert_eq!(score(Rounding::NearestEven), 62.0);
+    assert_eq!(score(Rounding::Floor), 62.0);
+    assert_eq!(score(Rounding::Ceil), 63.0);
+}
+
+#[test]
+fn whole_scores_dont_move_whatever_the_rounding() {
+    let text = fixture("misspelled.txt");
+    for rounding in [Rounding::Floor, Rounding::Ceil, Rounding::NearestEven] {
+        let config = configured(|c| c.rounding = rounding);
+        let rubric = Rubric::from_string_with_answers(&text, &config, true);
+        assert_eq!(rubric.score_percent(), 80.0);
+    }
+}
+
+#[test]
+fn filler_heavy_text_fails_the_heuristic() {
+    let text = "It is important to note that cats sleep a lot. Needless to say, they delve into naps. \
+When it comes to food, it is important to note that they eat fish.\n\nhttps://youtu.be/abc\n";
+    let config = configured(|c| c.max_filler_density = Some(1.0));
+    let rubric = Rubric::from_string_with_answers(text, &config, true);
+    assert_eq!(
+        graded(&rubric, "filler"),
+        (
+            0.0,
+            "5 in 34 words (14.7 per 100): 'it is important to note' x2, 'needless to say', 'delve into', 'when it comes to'"
+                .to_string()
+        )
+    );
+}
+
+#[test]
+fn concise_text_passes_the_filler_heuristic() {
+    let config = configured(|c| c.max_filler_density = Some(1.0));
+    let rubric = Rubric::from_string_with_answers(&fixture("clean.txt"), &config, true);
+    assert_eq!(graded(&rubric, "filler"), (1.0, "none found".to_string()));
+}
+
+fn sectioned() -> Config {
+    toml::from_str(
+        r#"
+        [[sections]]
+        name = "intro"
+        files = "intro*.txt"
+        [[sections]]
+        name = "body"
+        files = "body.txt"
+        weight = 2.0
+        [[sections]]
+        name = "conclusion"
+        files = "conclusion.txt"
+        "#,
+    )
+    .unwrap()
+}
+
+#[test]
+fn sections_are_graded_on_their_own_and_weighted() {
+    let (clean, misspelled) = (fixture("clean.txt"), fixture("misspelled.txt"));
+    let files = [
+        ("essay/intro-v2.txt", clean.as_str()),
+        ("essay/body.txt", misspelled.as_str()),
+        ("essay/conclusion.txt", clean.as_str()),
+        ("essay/notes.txt", misspelled.as_str()),
+    ];
+    let rubric = grade_sections(&files, &sectioned());
+    let body = grade_text(&misspelled, &sectioned()).score_percent();
+    expect(
+        &rubric,
+        &[
+            ("intro", 1.0),
+            ("body", body as f64 / 100.0),
+            ("conclusion", 1.0),
+        ],
+    );
+    let json = report(&rubric);
+    let sections = json["sections"].as_array().unwrap();
+    let files: Vec<&Value> = sections.iter().map(|s| &s["files"][0]).collect();
+    assert_eq!(
+        files,
+        [
+            "essay/intro-v2.txt",
+            "essay/body.txt",
+            "essay/conclusion.txt"
+        ]
+    );
+    assert_eq!(sections[1]["weight"], 2.0);
+    assert_eq!(
+        graded(&rubric, "body").1,
+        format!("{}%, short on spelling", body)
+    );
+    assert_eq!(rubric.score_percent(), ((200.0 + 2.0 * body) / 4.0).round());
+}
+
+#[test]
+fn a_section_without_a_file_gets_nothing() {
+    let clean = fixture("clean.txt");
+    let rubric = grade_sections(&[("intro.txt", clean.as_str())], &sectioned());
+    expect(
+        &rubric,
+        &[("intro", 1.0), ("body", 0.0), ("conclusion", 0.0)],
+    );
+    assert_eq!(
+        graded(&rubric, "conclusion").1,
+        "no file matches conclusion.txt"
+    );
+    assert_eq!(rubric.score_percent(), 25.0);
+}
+
+#[test]
+fn show_rubric_lists_what_gets_graded() {
+    let config = configured(|c| {
+        c.min_paragraphs = 2;
+        c.require_title = true;
+        c.max_tense_mix = Some(0.2);
+        c.voice = Some(Voice::Third);
+        c.require_balanced_quotes = true;
+        c.keyword_sets = vec![KeywordSet {
+            name: "pets".to_string(),
+            keywords: vec!["cats".to_string()],
+            min: None,
+        }];
+    });
+    let shown: Vec<String> = config
+        .criteria()
+        .into_iter()
+        .filter(|c| c.enabled)
+        .map(|c| c.name)
+        .collect();
+    let rubric = Rubric::from_string_with_answers(&fixture("clean.txt"), &config, true);
+    let graded: Vec<String> = grades(&rubric).into_iter().map(|(name, _)| name).collect();
+    assert_eq!(shown, graded);
+}
+
+#[test]
+fn show_rubric_lists_the_sections_and_their_share() {
+    let config = sectioned();
+    let text = config.show_rubric();
+    assert!(
+        text.ends_with(
+            "intro(25%): intro*.txt\n\
+            \tlink, spelling, punctuation, capitalization, questions\n\
+            body(50%): body.txt\n\
+            \tlink, spelling, punctuation, capitalization, questions\n\
+            conclusion(25%): conclusion.txt\n\
+            \tlink, spelling, punctuation, capitalization, questions\n"
+        ),
+        "{}",
+        text
+    );
+    let json: Value = serde_json::from_str(&config.show_rubric_json()).unwrap();
+    assert_eq!(json["sections"][1]["files"], "body.txt");
+    assert_eq!(json["sections"][1]["weight"], 50.0);
+    assert_eq!(json["sections"][1]["criteria"][0], "link");
+    // without sections the preview has no such key
+    let json: Value = serde_json::from_str(&Config::default().show_rubric_json()).unwrap();
+    assert!(json.get("sections").is_none());
+}
+
+#[test]
+fn forbidden_words_match_whole_words_only() {
+    let config = configured(|c| c.forbidden_words = vec!["ass".to_string()]);
+    // inside "class", "pass" and "assess" it isn't the word
+    let rubric = grade_text("We met after class to pass and assess.\n", &config);
+    assert_eq!(
+        graded(&rubric, "forbidden_words"),
+        (1.0, "none found".to_string())
+    );
+    let rubric = grade_text("He called him an ass. Then he left class.\n", &config);
+    assert_eq!(
+        graded(&rubric, "forbidden_words"),
+        (0.0, "'ass' at 1:18".to_string())
+    );
+}
+
+#[test]
+fn one_of_two_links_earns_half_the_link_criterion() {
+    let config = configured(|c| c.min_links = 2);
+    let rubric = Rubric::from_string_with_answers(&fixture("clean.txt"), &config, true);
+    assert_eq!(graded(&rubric, "link").0, 0.5);
+    // the link is worth 20% of the score, half of it is 10%
+    assert_eq!(rubric.score_percent(), 90.0);
+}
+
+#[test]
+fn duplicate_links_count_once() {
+    let config = configured(|c| c.min_links = 2);
+    let text = "My video is about cats. Cats are great pets and they sleep a lot.\n\n\
+        https://youtu.be/abc123 https://youtu.be/abc123 http://YOUTU.BE/abc123/ \
+        https://youtu.be/abc123#t=10";
+    let rubric = Rubric::from_string_with_answers(text, &config, true);
+    assert_eq!(
+        graded(&rubric, "link"),
+        (0.5, "1 of 2 links, 1 unique of 4 pasted".to_string())
+    );
+}
+
+#[test]
+fn only_the_forbidden_link_fails_the_link_criterion() {
+    let config = configured(|c| c.forbidden_link_domains = vec!["tiktok.com".to_string()]);
+    let text = "My video is about cats. Cats are great pets and they sleep a lot.\n\n\
+        https://youtu.be/abc123 https://example.com/cats";
+    let rubric = Rubric::from_string_with_answers(text, &config, true);
+    assert_eq!(graded(&rubric, "link").0, 1.0);
+    let text = format!("{} https://www.tiktok.com/@cats/video/1", text);
+    let rubric = Rubric::from_string_with_answers(&text, &config, true);
+    assert_eq!(
+        graded(&rubric, "link"),
+        (
+            0.0,
+            "forbidden: https://www.tiktok.com/@cats/video/1".to_string()
+        )
+    );
+}
+
+#[test]
+fn rubric_round_trips_through_json() {
+    let config = configured(|c| {
+        c.min_links = 2;
+        c.min_paragraphs = 2;
+    });
+    let rubric = Rubric::from_string_with_answers(&fixture("misspelled.txt"), &config, true);
+    let json = serde_json::to_string(&rubric).unwrap();
+    let read: Rubric = serde_json::from_str(&json).unwrap();
+    let fields = |rubric: &Rubric| -> Vec<(String, f64, String, String)> {
+        report(rubric)["criteria"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|c| {
+                (
+                    c["name"].as_str().unwrap().to_string(),
+                    c["grade"].as_f64().unwrap(),
+                    c["status"].as_str().unwrap().to_string(),
+                    c["detail"].as_str().unwrap().to_string(),
+                )
+            })
+            .collect()
+    };
+    assert_eq!(fields(&read), fields(&rubric));
+    assert_eq!(graded(&read, "link"), (0.5, "1 of 2 links".to_string()));
+    assert_eq!(read.score_percent(), rubric.score_percent());
+    // a criterion that wasn't evaluated stays that way, not a 0
+    let rubric = grade_text(&fixture("clean.txt"), &Config::default());
+    let read: Rubric = serde_json::from_str(&serde_json::to_string(&rubric).unwrap()).unwrap();
+    assert_eq!(criterion(&read, "questions")["status"], "n/a");
+    assert_eq!(read.score_percent(), 100.0);
+}
+
+#[test]
+fn config_hash_covers_the_answer_key_and_prompt() {
+    let hash = Config::default().hash();
+    let keyed = configured(|c| c.expected_answers = KeyAnswer::parse_key("chlorophyll").unwrap());
+    assert_ne!(keyed.hash(), hash);
+    let rekeyed = configured(|c| c.expected_answers = KeyAnswer::parse_key("glucose").unwrap());
+    assert_ne!(rekeyed.hash(), keyed.hash());
+    let prompt = configured(|c| c.prompt = Some("Describe your pet.".to_string()));
+    assert_ne!(prompt.hash(), hash);
+    let previous = configured(|c| c.previous = Some("My first draft.".to_string()));
+    assert_ne!(previous.hash(), hash);
+    assert_eq!(Config::default().hash(), hash);
+}
+
+#[test]
+fn config_round_trips_through_toml() {
+    let config = configured(|c| {
+        c.min_paragraphs = 3;
+        c.forbidden_words = vec!["very".to_string()];
+        c.voice = Some(Voice::Third);
+        c.rounding = Rounding::Floor;
+        c.linters = Linters::default().disable("Spelling");
+    });
+    let text = toml::to_string(&config).unwrap();
+    let read: Config = toml::from_str(&text).unwrap();
+    assert_eq!(
+        serde_json::to_value(&read).unwrap(),
+        serde_json::to_value(&config).unwrap()
+    );
+    // a misspelled key is an error, not a setting silently left at its default
+    let error = toml::from_str::<Config>("min_paragraph = 3\n").unwrap_err();
+    assert!(
+        error.to_string().contains("unknown field `min_paragraph`"),
+        "{}",
+        error
+    );
+}
+
+fn paragraphs(text: &str) -> (f64, String) {
+    let config = configured(|c| c.min_paragraphs = 2);
+    graded(&grade_text(text, &config), "paragraphs")
+}
+
+#[test]
+fn one_paragraph_is_too_few() {
+    let text = "Cats sleep a lot.\nThey also eat fish.\n";
+    assert_eq!(paragraphs(text), (0.0, "1 found".to_string()));
+    // indenting a line doesn't start a paragraph, only a blank line does
+    let text = "Cats sleep a lot.\n    They also eat fish.\n";
+    assert_eq!(paragraphs(text), (0.0, "1 found".to_string()));
+}
+
+#[test]
+fn blank_lines_separate_paragraphs() {
+    let text = "Cats sleep a lot.\n\nThey also eat fish.\n\nDogs don't.\n";
+    assert_eq!(paragraphs(text), (1.0, "3 found".to_string()));
+    // several blank lines, or ones with only spaces in them, are one break
+    let text = "\n\nCats sleep a lot.\n\n\n\nThey also eat fish.\n  \t\nDogs don't.\n\n";
+    assert_eq!(paragraphs(text), (1.0, "3 found".to_string()));
+}
+
+fn title(text: &str, change: impl FnOnce(&mut Config)) -> (f64, String) {
+    let config = configured(|c| {
+        c.require_title = true;
+        change(c);
+    });
+    graded(&grade_text(text, &config), "title")
+}
+
+#[test]
+fn a_short_first_line_is_the_title() {
+    // blank lines above it are skipped
+    let text = "\n  Why Cats Sleep So Much\n\nCats sleep a lot. They also eat fish.\n";
+    assert_eq!(
+        title(text, |_| ()),
+        (1.0, "found 'Why Cats Sleep So Much'".to_string())
+    );
+    let text = "Essay: cats\n\nCats sleep a lot.\n";
+    let pattern = |c: &mut Config| c.title_pattern = Some("^Essay: ".to_string());
+    assert_eq!(
+        title(text, pattern),
+        (1.0, "found 'Essay: cats'".to_string())
+    );
+}
+
+#[test]
+fn a_sentence_or_a_lowercase_line_isnt_a_title() {
+    let untitled = (0.0, "no title found".to_string());
+    // a sentence ending in a period
+    assert_eq!(
+        title("Cats sleep a lot.\n\nThey eat fish.\n", |_| ()),
+        untitled
+    );
+    // starting lowercase
+    assert_eq!(
+        title("why cats sleep\n\nCats sleep a lot.\n", |_| ()),
+        untitled
+    );
+    // over title_max_words
+    let long = "Why My Two Cats Sleep All Day Long And Never Seem To Wake Up\n\nThey do.\n";
+    assert_eq!(title(long, |_| ()), untitled);
+    // strict wants every word over three letters capitalized
+    let text = "Why cats sleep\n\nCats sleep a lot.\n";
+    assert_eq!(title(text, |_| ()).0, 1.0);
+    let strict = |c: &mut Config| c.title_strictness = TitleStrictness::Strict;
+    assert_eq!(title(text, strict), untitled);
+}
+
+#[test]
+fn control_characters_are_dropped_before_grading() {
+    let clean = fixture("clean.txt");
+    // a null byte, a vertical tab and a bell pasted in with the text
+    let pasted = clean
+        .replace("cats.", "cats.\0\u{b}")
+        .replace("pets", "pe\u{7}ts");
+    let config = configured(|c| c.auto_questions = true);
+    let rubric = Rubric::from_reader(pasted.as_bytes(), &config).unwrap();
+    let expected = grade_text(&clean, &config);
+    assert_eq!(report(&rubric)["errors"], report(&expected)["errors"]);
+    assert_eq!(rubric.score_percent(), 100.0);
+}
+
+fn tense(text: &str) -> (f64, String) {
+    let config = configured(|c| c.max_tense_mix = Some(0.2));
+    graded(&grade_text(text, &config), "tense")
+}
+
+#[test]
+fn past_tense_throughout_passes() {
+    let text = "We walked to the park. The dog chased a ball and barked. \
+Then it rained, so we hurried home and dried off.\n";
+    assert_eq!(
+        tense(text),
+        (
+            1.0,
+            "5 past, 0 present verbs, 0% in the other tense".to_string()
+        )
+    );
+}
+
+#[test]
+fn switching_tenses_fails() {
+    let text = "The park was quiet when we arrived. Now the dog is happy and the birds are loud. \
+We were tired, but the sun is out and the grass is green.\n";
+    assert_eq!(
+        tense(text),
+        (
+            0.0,
+            "3 past, 4 present verbs, 43% in the other tense".to_string()
+        )
+    );
+}
+
+#[test]
+fn accents_cjk_and_emoji_are_counted_by_unicode_words() {
+    let words = |text: &str| readability_words(text, HyphenatedWords::Parts, Contractions::One);
+    // accented words stay whole, composed or not
+    assert_eq!(words("Café naïve résumé, déjà vu.\n"), 5);
+    assert_eq!(words("Cafe\u{301} nai\u{308}ve.\n"), 2);
+    // no spaces between CJK words, every character is one
+    assert_eq!(words("我喜欢猫。猫很可爱。\n"), 8);
+    // emoji aren't words
+    assert_eq!(words("Cats 🐱 are great 🎉👍🏽 pets.\n"), 4);
+}
+
+#[test]
+fn every_length_criterion_takes_accents_cjk_and_emoji() {
+    let config = configured(|c| {
+        c.min_paragraphs = 2;
+        c.max_sentence_words = 3;
+        c.min_sentences_per_answer = 1;
+        c.min_grade_level = Some(0.0);
+        c.max_same_opener = Some(0.5);
+        c.max_paragraph_share = Some(0.9);
+        c.max_filler_density = Some(1.0);
+        c.require_title = true;
+    });
+    let text = "Café 🎉\n\n我喜欢猫。猫很可爱。👍🏽\n\nre\u{301}sume\u{301} 🐱🐱 naïve.\n";
+    let rubric = grade_text(text, &config);
+    assert_eq!(graded(&rubric, "paragraphs").1, "3 found");
+    assert_eq!(graded(&rubric, "title").1, "found 'Café 🎉'");
+    assert_eq!(graded(&rubric, "filler").1, "none found");
+    // café, eight characters, résumé and naïve
+    assert_eq!(report(&rubric)["readability"]["words"], 11);
+}
+
+fn openers(text: &str) -> (f64, String) {
+    let config = configured(|c| c.max_same_opener = Some(0.3));
+    graded(&grade_text(text, &config), "openers")
+}
+
+#[test]
+fn sentences_all_starting_alike_fail() {
+    let text = "I like cats. I feed them every day. I think they like me. \
+I wrote this essay about them.\n";
+    assert_eq!(
+        openers(text),
+        (0.0, "'i' starts 4 of 4 sentences".to_string())
+    );
+}
+
+#[test]
+fn varied_openers_pass() {
+    let text = "I like cats. Every day they get fed. They seem to like me. \
+This essay is about them.\n";
+    assert_eq!(
+        openers(text),
+        (1.0, "'i' starts 1 of 4 sentences".to_string())
+    );
+    // too few sentences to tell
+    assert_eq!(
+        openers("I like cats. I feed them.\n"),
+        (1.0, "only 2 sentences".to_string())
+    );
+}
+
+fn keywords(text: &str, min: Option<usize>) -> (f64, String) {
+    let config = configured(|c| {
+        c.keyword_sets = vec![KeywordSet {
+            name: "concepts".to_string(),
+            keywords: [
+                "photosynthesis",
+                "chlorophyll",
+                "sunlight",
+                "carbon dioxide",
+            ]
+            .map(String::from)
+            .to_vec(),
+            min,
+        }]
+    });
+    graded(
+        &Rubric::from_string_with_answers(text, &config, true),
+        "concepts",
+    )
+}
+
+#[test]
+fn some_keywords_earn_part_of_the_criterion() {
+    let text = "Plants need sunlight. Chlorophyll makes leaves green.";
+    assert_eq!(
+        keywords(text, None),
+        (
+            0.5,
+            "found: chlorophyll, sunlight; missing: photosynthesis, carbon dioxide".to_string()
+        )
+    );
+    // grades are f32, the report widens them
+    assert_eq!(keywords(text, Some(3)).0, (2.0_f32 / 3.0) as f64);
+}
+
+#[test]
+fn enough_keywords_earn_the_whole_criterion() {
+    let text = "Photosynthesis needs sunlight and carbon dioxide. Chlorophyll makes leaves green.";
+    assert_eq!(
+        keywords(text, None),
+        (
+            1.0,
+            "found: photosynthesis, chlorophyll, sunlight, carbon dioxide".to_string()
+        )
+    );
+    // more than min doesn't earn more than full marks
+    assert_eq!(keywords(text, Some(3)).0, 1.0);
+}